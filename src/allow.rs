@@ -0,0 +1,204 @@
+use crate::callback::{Callback, Context};
+use crate::path::Path;
+use crate::replay::{Collect, Event, EventKind};
+use crate::value::Value;
+use crate::Deserializer;
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem;
+use serde::de::{
+    self, Deserialize, DeserializeSeed, IgnoredAny, IntoDeserializer, SeqAccess, Visitor,
+};
+use serde::ser::{Serialize, Serializer};
+
+/// Name of the newtype struct through which `Allow` asks the [`Deserializer`]
+/// to stop tracking its content.
+///
+/// [`Deserializer`]: crate::Deserializer
+pub(crate) const NAME: &str = "serde_ignored::Allow";
+
+/// Deserializes `T` without reporting anything that it ignores.
+///
+/// Use this for parts of the input that are open on purpose, such as a
+/// `metadata` table or a section of `x-*` extensions, where the keys that `T`
+/// does not know about are expected.
+///
+/// ```
+/// # use serde_derive::Deserialize;
+/// # use std::collections::BTreeMap as Map;
+/// #
+/// #[derive(Deserialize)]
+/// struct Package {
+///     name: String,
+///     metadata: serde_ignored::Allow<Metadata>,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Metadata {
+///     docs: Option<String>,
+/// }
+///
+/// let j = r#"{"name": "demo", "metadata": {"docs": "none", "ci": {}}, "typo": 1}"#;
+/// let jd = &mut serde_json::Deserializer::from_str(j);
+/// let mut unused = Vec::new();
+///
+/// let package: Package = serde_ignored::deserialize(jd, |path| {
+///     unused.push(path.to_string());
+/// })?;
+///
+/// assert_eq!(package.metadata.0.docs.as_deref(), Some("none"));
+/// assert_eq!(unused, ["typo"]);
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+///
+/// What `T` ignores is still kept by [`deserialize_preserving`], which
+/// reads the content into a [`Value`] first in order to do so. `T` cannot
+/// borrow from the input in that case.
+///
+/// [`deserialize_preserving`]: crate::deserialize_preserving
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Allow<T>(pub T);
+
+impl<'de, T> Deserialize<'de> for Allow<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(NAME, AllowVisitor(PhantomData))
+    }
+}
+
+/// Serializes as the `T` inside, so that a type with an `Allow` field can be
+/// written back out.
+impl<T> Serialize for Allow<T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+struct AllowVisitor<T>(PhantomData<T>);
+
+impl<'de, T> Visitor<'de> for AllowVisitor<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = Allow<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any value")
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(Allow)
+    }
+
+    // Reached through serde_ignored's Deserializer for a callback that
+    // captures allowed values, which replays the content as a sequence of
+    // one element.
+    fn visit_seq<S>(self, mut seq: S) -> Result<Self::Value, S::Error>
+    where
+        S: SeqAccess<'de>,
+    {
+        let allowed = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        // Lets the deserializer pass on what was ignored.
+        seq.next_element::<IgnoredAny>()?;
+        Ok(Allow(allowed))
+    }
+}
+
+/// Accepts and discards any value without it being reported as ignored.
+///
+/// This is like [`IgnoredAny`], for fields whose content is skipped on
+/// purpose.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Intentional;
+
+impl<'de> Deserialize<'de> for Intentional {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let Allow(IgnoredAny) = Allow::deserialize(deserializer)?;
+        Ok(Intentional)
+    }
+}
+
+/// Seq access that replays the content of an `Allow` to the `T` inside of it,
+/// then passes on the values that it ignored but not its missing fields or
+/// duplicate keys, for a callback that captures allowed values.
+pub(crate) struct Content<'a, 'b, F: 'b, E> {
+    value: Option<Value>,
+    callback: &'b mut F,
+    path: Path<'a>,
+    context: Context<'static>,
+    events: Vec<Event>,
+    marker: PhantomData<E>,
+}
+
+impl<'a, 'b, F, E> Content<'a, 'b, F, E> {
+    pub(crate) fn new(
+        value: Value,
+        callback: &'b mut F,
+        path: Path<'a>,
+        context: Context<'static>,
+    ) -> Self {
+        Content {
+            value: Some(value),
+            callback,
+            path,
+            context,
+            events: Vec::new(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, 'b, 'de, F, E> SeqAccess<'de> for Content<'a, 'b, F, E>
+where
+    F: Callback,
+    E: de::Error,
+{
+    type Error = E;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, E>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let Some(value) = self.value.take() else {
+            for event in mem::take(&mut self.events) {
+                if let EventKind::Ignored(_) = event.kind {
+                    event.report(self.callback, &self.path)?;
+                }
+            }
+            return Ok(None);
+        };
+        let mut collect = Collect::new(&mut self.events, self.callback);
+        seed.deserialize(Deserializer {
+            de: value.into_deserializer(),
+            callback: &mut collect,
+            path: self.path.clone(),
+            context: self.context,
+            skipped: None,
+        })
+        .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(usize::from(self.value.is_some()))
+    }
+}
@@ -0,0 +1,474 @@
+use crate::key::Key;
+use crate::kind::{Kind, Shape};
+use crate::path::{Path, Segment};
+use crate::suggest;
+use crate::value::Value;
+use core::fmt::Display;
+use serde::de;
+
+/// Receives the path to every ignored value, together with context about the
+/// place it was ignored.
+///
+/// Any `FnMut(Path)` closure is a `Callback` that disregards the context. To
+/// receive the context from a closure, use [`deserialize_with_context`].
+///
+/// [`deserialize_with_context`]: crate::deserialize_with_context
+pub trait Callback {
+    /// Called before the value at `path` is skipped, or after it has been
+    /// consumed if [`inspects_values`] returns true. Returning an error aborts
+    /// deserialization with that error.
+    ///
+    /// [`inspects_values`]: Callback::inspects_values
+    fn ignored<E>(&mut self, path: Path, context: &Context) -> Result<(), E>
+    where
+        E: de::Error;
+
+    /// Whether the [`Context`] passed to [`ignored`] should describe the kind
+    /// and size of the ignored value. This requires reading through the value
+    /// rather than letting the input skip it, which fails in a format that is
+    /// not self-describing.
+    ///
+    /// [`ignored`]: Callback::ignored
+    fn inspects_values(&self) -> bool {
+        false
+    }
+
+    /// Whether ignored values should be captured and passed to
+    /// [`ignored_value`] instead of being skipped and passed to [`ignored`].
+    /// Like [`inspects_values`], this fails in a format that is not
+    /// self-describing.
+    ///
+    /// [`inspects_values`]: Callback::inspects_values
+    /// [`ignored_value`]: Callback::ignored_value
+    /// [`ignored`]: Callback::ignored
+    fn captures_values(&self) -> bool {
+        false
+    }
+
+    /// Called with a copy of the value at `path` after it has been consumed
+    /// from the input, if [`captures_values`] returns true.
+    ///
+    /// [`captures_values`]: Callback::captures_values
+    fn ignored_value<E>(&mut self, path: Path, context: &Context, value: Value) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        let _ = value;
+        self.ignored(path, context)
+    }
+
+    /// Whether values ignored inside of an [`Allow`] should be captured and
+    /// passed to [`ignored_value`] too, for a callback that keeps ignored
+    /// values rather than reporting them. Only consulted if
+    /// [`captures_values`] returns true.
+    ///
+    /// [`Allow`]: crate::Allow
+    /// [`ignored_value`]: Callback::ignored_value
+    /// [`captures_values`]: Callback::captures_values
+    fn captures_allowed(&self) -> bool {
+        false
+    }
+
+    /// Whether [`missing`] should be called for struct fields that do not
+    /// appear in the input.
+    ///
+    /// [`missing`]: Callback::missing
+    fn tracks_missing(&self) -> bool {
+        false
+    }
+
+    /// Called with the path to each field of a struct or struct variant that
+    /// had no entry in the map it was deserialized from, once the struct's
+    /// `Deserialize` impl has read the last entry, if [`tracks_missing`]
+    /// returns true.
+    ///
+    /// [`tracks_missing`]: Callback::tracks_missing
+    fn missing<E>(&mut self, path: Path, context: &Context) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        let _ = (path, context);
+        Ok(())
+    }
+
+    /// Whether [`duplicate`] should be called for keys that appear more than
+    /// once in the same map.
+    ///
+    /// [`duplicate`]: Callback::duplicate
+    fn tracks_duplicates(&self) -> bool {
+        false
+    }
+
+    /// Called with the path to a map entry whose key was repeated later in
+    /// the same map, if [`tracks_duplicates`] returns true. Most `Deserialize`
+    /// impls for maps keep the last of the entries, so the one at `path` is
+    /// lost. A key repeated three times is reported twice.
+    ///
+    /// [`tracks_duplicates`]: Callback::tracks_duplicates
+    fn duplicate<E>(&mut self, path: Path, context: &Context) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        let _ = (path, context);
+        Ok(())
+    }
+}
+
+impl<F> Callback for F
+where
+    F: FnMut(Path),
+{
+    fn ignored<E>(&mut self, path: Path, context: &Context) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        let _ = context;
+        self(path);
+        Ok(())
+    }
+}
+
+/// Information about the Rust type whose `Deserialize` impl ignored a value.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Context<'a> {
+    container: Option<&'static str>,
+    fields: Option<&'static [&'static str]>,
+    shape: Option<Shape>,
+    reason: Reason,
+    variant: Option<&'a str>,
+}
+
+impl<'a> Context<'a> {
+    pub(crate) fn with_container(mut self, container: &'static str) -> Self {
+        self.container = Some(container);
+        self
+    }
+
+    pub(crate) fn with_fields(mut self, fields: &'static [&'static str]) -> Self {
+        self.fields = Some(fields);
+        self
+    }
+
+    pub(crate) fn with_reason(mut self, reason: Reason) -> Self {
+        self.reason = reason;
+        self
+    }
+
+    pub(crate) fn with_shape(mut self, shape: Shape) -> Self {
+        self.shape = Some(shape);
+        self
+    }
+
+    pub(crate) fn with_variant(self, variant: &str) -> Context<'_> {
+        Context {
+            variant: Some(variant),
+            ..self.without_variant()
+        }
+    }
+
+    pub(crate) fn without_variant(self) -> Context<'static> {
+        Context {
+            container: self.container,
+            fields: self.fields,
+            shape: self.shape,
+            reason: self.reason,
+            variant: None,
+        }
+    }
+
+    /// Name of the struct, tuple struct or enum that ignored the value, as
+    /// given to `deserialize_struct` and friends. For an ignored field
+    /// `dependencies.serde.typo1` this would be the name of the type at
+    /// `dependencies.serde`.
+    ///
+    /// `None` if the value was ignored by something that does not pass a
+    /// name, such as a map or a struct deserialized through `deserialize_map`
+    /// because it contains a `#[serde(flatten)]` field.
+    #[must_use]
+    pub fn container(&self) -> Option<&'static str> {
+        self.container
+    }
+
+    /// Fields that the struct or struct variant would have accepted, as given
+    /// to `deserialize_struct` or `struct_variant`. This is the same list that
+    /// serde's own "unknown field" error prints after "expected one of".
+    ///
+    /// `None` if the value was ignored by something other than a struct or
+    /// struct variant, in which case there is no list of expected keys.
+    #[must_use]
+    pub fn expected_fields(&self) -> Option<&'static [&'static str]> {
+        self.fields
+    }
+
+    /// The expected field that the last key of `path` was most likely meant
+    /// to be, for a "did you mean" hint.
+    ///
+    /// Keys are compared ignoring ASCII case and the difference between `-`
+    /// and `_`, and otherwise by edit distance. `None` if `path` does not end
+    /// in a string key, if there are no [expected fields], if the key is
+    /// itself one of them, or if none of them is close enough.
+    ///
+    /// [expected fields]: Context::expected_fields
+    #[must_use]
+    pub fn suggestion(&self, path: &Path) -> Option<&'static str> {
+        let key = match path.segment()? {
+            Segment::Map { key } => key.as_str()?,
+            _ => return None,
+        };
+        let fields = self.fields?;
+        if fields.contains(&key) {
+            // A field that the struct skips on purpose, such as one of type
+            // IgnoredAny.
+            return None;
+        }
+        suggest::closest(key, fields)
+    }
+
+    /// Why the value was ignored.
+    #[must_use]
+    pub fn reason(&self) -> Reason {
+        self.reason
+    }
+
+    /// What kind of value was ignored.
+    ///
+    /// `None` unless the callback [inspects values], as the closure given to
+    /// [`deserialize_inspecting`] does.
+    ///
+    /// [inspects values]: Callback::inspects_values
+    /// [`deserialize_inspecting`]: crate::deserialize_inspecting
+    #[must_use]
+    pub fn kind(&self) -> Option<Kind> {
+        self.shape.map(|shape| shape.kind)
+    }
+
+    /// Number of elements or entries in the ignored value, if it is a
+    /// sequence or map. An enum counts as a map with one entry.
+    #[must_use]
+    pub fn size(&self) -> Option<usize> {
+        self.shape?.len
+    }
+
+    /// Name of the enum variant as it appeared in the input, if the value was
+    /// ignored because it is not one of the enum's variants.
+    ///
+    /// `None` unless the reason is [`Reason::OtherVariant`].
+    #[must_use]
+    pub fn variant(&self) -> Option<&'a str> {
+        self.variant
+    }
+}
+
+/// Why a value was ignored, as reported by [`Context::reason`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Reason {
+    /// Value of a key that is not among the fields of a struct.
+    UnknownField,
+    /// Value of a key that is not among the fields of a struct variant.
+    UnknownVariantField,
+    /// Value that the `Deserialize` impl asked to skip where it could have
+    /// been used, such as a field, map value or newtype of type
+    /// [`IgnoredAny`](serde::de::IgnoredAny).
+    #[default]
+    IgnoredAny,
+    /// Enum variant not known to the enum, which a `#[serde(other)]` variant
+    /// accepted in its place. The path is that of the enum, and the name of
+    /// the variant is in [`Context::variant`].
+    ///
+    /// This is a heuristic: a variant counts as unknown if its name is not
+    /// among the variants that the enum passed to `deserialize_enum`, so a
+    /// hand-written impl that accepts other spellings of its variants, such
+    /// as in a different case, has those reported too.
+    OtherVariant,
+    /// Element of a sequence that the `Deserialize` impl skipped after it
+    /// stopped using the sequence, typically the rest of a sequence after
+    /// the elements it needed, or an element past the length of a tuple.
+    TrailingData,
+}
+
+impl Reason {
+    pub(crate) fn of(path: &Path, context: &Context) -> Self {
+        match path {
+            Path::Map { parent, key } => match context.fields {
+                Some(fields) if !is_expected(key, fields) => match parent {
+                    Path::Variant { .. } => Reason::UnknownVariantField,
+                    _ => Reason::UnknownField,
+                },
+                _ => Reason::IgnoredAny,
+            },
+            // Whether an element is trailing depends on what the sequence
+            // visitor did before it, which only the SeqAccess knows.
+            Path::Seq { .. } => context.reason,
+            _ => Reason::IgnoredAny,
+        }
+    }
+}
+
+pub(crate) fn is_expected(key: &Key, names: &[&str]) -> bool {
+    position(key, names).is_some()
+}
+
+// Struct fields and enum variants are identified either by name or by index.
+pub(crate) fn position(key: &Key, names: &[&str]) -> Option<usize> {
+    match key {
+        Key::Str(key) => names.iter().position(|name| name == key),
+        Key::Bytes(key) => names.iter().position(|name| name.as_bytes() == *key),
+        Key::Uint(index) => usize::try_from(*index)
+            .ok()
+            .filter(|index| *index < names.len()),
+        Key::Int(index) => usize::try_from(*index)
+            .ok()
+            .filter(|index| *index < names.len()),
+        _ => None,
+    }
+}
+
+/// Adapts a closure that takes a `Context` into a `Callback`.
+pub(crate) struct WithContext<F>(pub F);
+
+impl<F> Callback for WithContext<F>
+where
+    F: FnMut(Path, &Context),
+{
+    fn ignored<E>(&mut self, path: Path, context: &Context) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        (self.0)(path, context);
+        Ok(())
+    }
+}
+
+/// Adapts a closure that takes a `Context` into a `Callback` that inspects
+/// each ignored value to describe its kind and size.
+pub(crate) struct Inspecting<F>(pub F);
+
+impl<F> Callback for Inspecting<F>
+where
+    F: FnMut(Path, &Context),
+{
+    fn ignored<E>(&mut self, path: Path, context: &Context) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        (self.0)(path, context);
+        Ok(())
+    }
+
+    fn inspects_values(&self) -> bool {
+        true
+    }
+}
+
+/// Adapts a closure that may reject an ignored value into a `Callback`. The
+/// rejection becomes a custom error of the deserializer, prefixed with the
+/// path.
+pub(crate) struct Fallible<F>(pub F);
+
+impl<F, T> Callback for Fallible<F>
+where
+    F: FnMut(&Path) -> Result<(), T>,
+    T: Display,
+{
+    fn ignored<E>(&mut self, path: Path, context: &Context) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        let _ = context;
+        (self.0)(&path).map_err(|err| E::custom(format_args!("{}: {}", path, err)))
+    }
+}
+
+/// Adapts a pair of closures, one for ignored values and one for missing
+/// fields, into a `Callback`.
+pub(crate) struct WithMissing<F, G>(pub F, pub G);
+
+impl<F, G> Callback for WithMissing<F, G>
+where
+    F: FnMut(Path),
+    G: FnMut(Path),
+{
+    fn ignored<E>(&mut self, path: Path, context: &Context) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        let _ = context;
+        (self.0)(path);
+        Ok(())
+    }
+
+    fn tracks_missing(&self) -> bool {
+        true
+    }
+
+    fn missing<E>(&mut self, path: Path, context: &Context) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        let _ = context;
+        (self.1)(path);
+        Ok(())
+    }
+}
+
+/// Adapts a pair of closures, one for ignored values and one for duplicate
+/// keys, into a `Callback`.
+pub(crate) struct WithDuplicates<F, G>(pub F, pub G);
+
+impl<F, G> Callback for WithDuplicates<F, G>
+where
+    F: FnMut(Path),
+    G: FnMut(Path),
+{
+    fn ignored<E>(&mut self, path: Path, context: &Context) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        let _ = context;
+        (self.0)(path);
+        Ok(())
+    }
+
+    fn tracks_duplicates(&self) -> bool {
+        true
+    }
+
+    fn duplicate<E>(&mut self, path: Path, context: &Context) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        let _ = context;
+        (self.1)(path);
+        Ok(())
+    }
+}
+
+/// Adapts a closure that takes the ignored value into a `Callback`.
+pub(crate) struct WithValue<F>(pub F);
+
+impl<F> Callback for WithValue<F>
+where
+    F: FnMut(Path, Value),
+{
+    fn ignored<E>(&mut self, path: Path, context: &Context) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        // Not called, because values are always captured.
+        let _ = (path, context);
+        Ok(())
+    }
+
+    fn captures_values(&self) -> bool {
+        true
+    }
+
+    fn ignored_value<E>(&mut self, path: Path, context: &Context, value: Value) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        let _ = context;
+        (self.0)(path, value);
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+use crate::path::PathBuf;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display};
+use serde::de::StdError;
+
+/// Error parsing a string representation of a path.
+pub struct ParseError {
+    msg: &'static str,
+    offset: usize,
+}
+
+impl ParseError {
+    pub(crate) fn new(msg: &'static str, offset: usize) -> Self {
+        ParseError { msg, offset }
+    }
+
+    /// Byte offset into the input at which the error was detected.
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{} at offset {}", self.msg, self.offset)
+    }
+}
+
+impl Debug for ParseError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("ParseError")
+            .field("msg", &self.msg)
+            .field("offset", &self.offset)
+            .finish()
+    }
+}
+
+impl StdError for ParseError {}
+
+/// Error returned by [`deserialize_strict`](crate::deserialize_strict).
+#[derive(Debug)]
+pub enum StrictError<E> {
+    /// The input could not be deserialized.
+    Deserialize(E),
+    /// The input was deserialized, but these parts of it were ignored. Never
+    /// empty.
+    Ignored(Vec<PathBuf>),
+}
+
+impl<E> StrictError<E> {
+    /// Paths to every ignored value, or an empty slice if deserialization
+    /// failed.
+    pub fn ignored(&self) -> &[PathBuf] {
+        match self {
+            StrictError::Deserialize(_) => &[],
+            StrictError::Ignored(paths) => paths,
+        }
+    }
+}
+
+impl<E> Display for StrictError<E>
+where
+    E: Display,
+{
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StrictError::Deserialize(err) => Display::fmt(err, formatter),
+            StrictError::Ignored(paths) => {
+                if let [path] = paths.as_slice() {
+                    return write!(formatter, "unknown key `{}`", path);
+                }
+                write!(formatter, "{} unknown keys: ", paths.len())?;
+                for (i, path) in paths.iter().enumerate() {
+                    if i > 0 {
+                        formatter.write_str(", ")?;
+                    }
+                    write!(formatter, "`{}`", path)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<E> StdError for StrictError<E>
+where
+    E: StdError + 'static,
+{
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            StrictError::Deserialize(err) => Some(err),
+            StrictError::Ignored(_) => None,
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use crate::callback::{Callback, Context, Reason};
+use crate::key::Key;
+use crate::path::{Path, PathBuf, SegmentBuf};
+use crate::value::Value;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use serde::de;
+
+/// Side table of ignored values keyed by their path, for writing them back
+/// out with [`Serializer`](crate::Serializer).
+///
+/// An `Extras` is a [`Callback`] that captures every ignored value, so it can
+/// be filled in by [`deserialize_preserving`] or by passing it to
+/// [`Deserializer::with_callback`].
+///
+/// [`deserialize_preserving`]: crate::deserialize_preserving
+/// [`Deserializer::with_callback`]: crate::Deserializer::with_callback
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Extras {
+    values: BTreeMap<PathBuf, Value>,
+}
+
+impl Extras {
+    #[must_use]
+    pub fn new() -> Self {
+        Extras {
+            values: BTreeMap::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// The ignored value at `path`, if any.
+    #[must_use]
+    pub fn get(&self, path: &PathBuf) -> Option<&Value> {
+        self.values.get(path)
+    }
+
+    /// Adds a value to be written at `path`, returning the value previously
+    /// stored there.
+    pub fn insert(&mut self, path: PathBuf, value: Value) -> Option<Value> {
+        self.values.insert(path, value)
+    }
+
+    pub fn remove(&mut self, path: &PathBuf) -> Option<Value> {
+        self.values.remove(path)
+    }
+
+    /// Iterates over the stored values in order of their paths.
+    pub fn iter(&self) -> impl Iterator<Item = (&PathBuf, &Value)> {
+        self.values.iter()
+    }
+
+    // Entries that belong directly inside the struct or map at `path`.
+    pub(crate) fn children(&self, path: &Path) -> Vec<(&Key, &Value)> {
+        if self.values.is_empty() {
+            return Vec::new();
+        }
+        let base = path.to_owned();
+        let depth = base.segments().len();
+        self.values
+            .range(&base..)
+            .take_while(|(child, _)| child.starts_with(&base))
+            .filter_map(|(child, value)| match child.segments() {
+                [segments @ .., SegmentBuf::Map { key }] if segments.len() == depth => {
+                    Some((key, value))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl Callback for Extras {
+    fn ignored<E>(&mut self, path: Path, context: &Context) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        // Not called, because values are always captured.
+        let _ = (path, context);
+        Ok(())
+    }
+
+    fn captures_values(&self) -> bool {
+        true
+    }
+
+    fn captures_allowed(&self) -> bool {
+        true
+    }
+
+    fn ignored_value<E>(&mut self, path: Path, context: &Context, value: Value) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        // A variant taken by #[serde(other)] is reported at the path of the
+        // enum, whose value the serializer already writes, so keeping the
+        // name would put it in the output twice.
+        if context.reason() != Reason::OtherVariant {
+            self.values.insert(path.to_owned(), value);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,223 @@
+use crate::callback::{Callback, Context};
+use crate::path::Path;
+use crate::replay::{Collect, Event, EventKind};
+use crate::value::Value;
+use crate::Deserializer;
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem;
+use serde::de::{
+    self, Deserialize, DeserializeSeed, IgnoredAny, IntoDeserializer, SeqAccess, Visitor,
+};
+
+/// Name of the newtype struct through which `Flatten` asks the
+/// [`Deserializer`] to replay the input to each side.
+pub(crate) const NAME: &str = "serde_ignored::Flatten";
+
+/// Deserializes two types from the same input, for use in place of
+/// `#[serde(flatten)]`.
+///
+/// Serde buffers the input of a struct that has a flattened field and replays
+/// it to the flattened field without going through the [`Deserializer`], so
+/// keys that are ignored inside of it never get reported. `Flatten<A, B>`
+/// instead reads the input into a [`Value`], deserializes both `A` and `B`
+/// from it, and reports the parts of the input that both of them ignored.
+///
+/// ```
+/// # use serde_derive::Deserialize;
+/// #
+/// #[derive(Deserialize)]
+/// struct Server {
+///     port: u16,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Tls {
+///     cert: String,
+/// }
+///
+/// let j = r#"{"port": 443, "cert": "server.pem", "typo": ""}"#;
+/// let jd = &mut serde_json::Deserializer::from_str(j);
+/// let mut unused = Vec::new();
+///
+/// let serde_ignored::Flatten(server, tls): serde_ignored::Flatten<Server, Tls> =
+///     serde_ignored::deserialize(jd, |path| unused.push(path.to_string()))?;
+///
+/// assert_eq!(server.port, 443);
+/// assert_eq!(tls.cert, "server.pem");
+/// assert_eq!(unused, ["typo"]);
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+///
+/// Because `A` and `B` are deserialized from a `Value`, they cannot borrow
+/// from the input.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Flatten<A, B>(pub A, pub B);
+
+impl<'de, A, B> Deserialize<'de> for Flatten<A, B>
+where
+    A: Deserialize<'de>,
+    B: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(NAME, FlattenVisitor(PhantomData))
+    }
+}
+
+struct FlattenVisitor<A, B>(PhantomData<(A, B)>);
+
+impl<'de, A, B> Visitor<'de> for FlattenVisitor<A, B>
+where
+    A: Deserialize<'de>,
+    B: Deserialize<'de>,
+{
+    type Value = Flatten<A, B>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any value")
+    }
+
+    // Reached without serde_ignored's Deserializer, from formats that handle
+    // newtype structs as their content.
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let value = Value::deserialize(deserializer)?;
+        let a = A::deserialize(value.clone().into_deserializer())?;
+        let b = B::deserialize(value.into_deserializer())?;
+        Ok(Flatten(a, b))
+    }
+
+    // Reached through serde_ignored's Deserializer, which replays the input as
+    // a sequence of two elements.
+    fn visit_seq<S>(self, mut seq: S) -> Result<Self::Value, S::Error>
+    where
+        S: SeqAccess<'de>,
+    {
+        let a = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let b = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        // Lets the deserializer report what both sides ignored.
+        seq.next_element::<IgnoredAny>()?;
+        Ok(Flatten(a, b))
+    }
+}
+
+/// Seq access that replays the same value to both sides of a `Flatten`, then
+/// reports what both of them ignored, along with the missing fields and
+/// duplicate keys that either of them found.
+pub(crate) struct Sides<'a, 'b, F: 'b, E> {
+    value: Value,
+    callback: &'b mut F,
+    path: Path<'a>,
+    context: Context<'static>,
+    events: [Vec<Event>; 2],
+    index: usize,
+    marker: PhantomData<E>,
+}
+
+impl<'a, 'b, F, E> Sides<'a, 'b, F, E>
+where
+    F: Callback,
+    E: de::Error,
+{
+    pub(crate) fn new(
+        value: Value,
+        callback: &'b mut F,
+        path: Path<'a>,
+        context: Context<'static>,
+    ) -> Self {
+        Sides {
+            value,
+            callback,
+            path,
+            context,
+            events: [Vec::new(), Vec::new()],
+            index: 0,
+            marker: PhantomData,
+        }
+    }
+
+    // A path that one side ignored is reported if the other side ignored it
+    // too, or ignored one of its ancestors. Of two such paths, only the deeper
+    // one is reported. Missing fields and duplicate keys are reported from
+    // either side, once.
+    fn report(&mut self) -> Result<(), E> {
+        let [a, b] = mem::take(&mut self.events);
+        let mut reported = Vec::new();
+        for x in &a {
+            let report = match x.kind {
+                EventKind::Ignored(_) => b.iter().any(|y| {
+                    matches!(y.kind, EventKind::Ignored(_)) && x.path.starts_with(&y.path)
+                }),
+                EventKind::Missing | EventKind::Duplicate => true,
+            };
+            if report {
+                reported.push(x.clone());
+            }
+        }
+        for y in &b {
+            let report = match y.kind {
+                EventKind::Ignored(_) => a.iter().any(|x| {
+                    matches!(x.kind, EventKind::Ignored(_))
+                        && y.path.starts_with(&x.path)
+                        && y.path != x.path
+                }),
+                EventKind::Missing | EventKind::Duplicate => !a.iter().any(|x| {
+                    mem::discriminant(&x.kind) == mem::discriminant(&y.kind) && x.path == y.path
+                }),
+            };
+            if report {
+                reported.push(y.clone());
+            }
+        }
+        reported.sort_by(|x, y| x.path.cmp(&y.path));
+        for event in reported {
+            event.report(self.callback, &self.path)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, 'b, 'de, F, E> SeqAccess<'de> for Sides<'a, 'b, F, E>
+where
+    F: Callback,
+    E: de::Error,
+{
+    type Error = E;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, E>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let index = self.index;
+        self.index += 1;
+        let value = match index {
+            0 => self.value.clone(),
+            1 => mem::replace(&mut self.value, Value::Unit),
+            2 => return self.report().map(|()| None),
+            _ => return Ok(None),
+        };
+        let mut collect = Collect::new(&mut self.events[index], self.callback);
+        seed.deserialize(Deserializer {
+            de: value.into_deserializer(),
+            callback: &mut collect,
+            path: self.path.clone(),
+            context: self.context,
+            skipped: None,
+        })
+        .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(2usize.saturating_sub(self.index))
+    }
+}
@@ -0,0 +1,611 @@
+use crate::error::ParseError;
+use crate::key::Key;
+use crate::path::{Path, PathBuf, Segment, SegmentBuf};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Write as _};
+
+/// Style in which to render a path, for use with [`Path::display_with`].
+///
+/// The path is presented to the formatter one segment at a time from the root
+/// to the leaf. Segments for which [`skip_segment`] returns true are left out
+/// entirely, and if nothing remains then [`write_root`] is called instead.
+///
+/// [`skip_segment`]: PathFormatter::skip_segment
+/// [`write_root`]: PathFormatter::write_root
+pub trait PathFormatter {
+    /// Writes a path that consists of no segments.
+    fn write_root(&self, formatter: &mut fmt::Formatter) -> fmt::Result;
+
+    /// Writes one segment. `first` is true for the first segment that is
+    /// written for the path, which usually needs no separator in front of it.
+    fn write_segment(
+        &self,
+        formatter: &mut fmt::Formatter,
+        segment: Segment,
+        first: bool,
+    ) -> fmt::Result;
+
+    /// Whether to leave this segment out of the rendered path.
+    fn skip_segment(&self, segment: Segment) -> bool {
+        let _ = segment;
+        false
+    }
+}
+
+impl<'a, F> PathFormatter for &'a F
+where
+    F: ?Sized + PathFormatter,
+{
+    fn write_root(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        (**self).write_root(formatter)
+    }
+
+    fn write_segment(
+        &self,
+        formatter: &mut fmt::Formatter,
+        segment: Segment,
+        first: bool,
+    ) -> fmt::Result {
+        (**self).write_segment(formatter, segment, first)
+    }
+
+    fn skip_segment(&self, segment: Segment) -> bool {
+        (**self).skip_segment(segment)
+    }
+}
+
+/// Dot-separated style that quotes keys the way TOML does, like
+/// `servers.0."example.com"`.
+///
+/// Keys made up of only ASCII letters, digits, `_` and `-` are written bare.
+/// Any other key, including the empty key, is written as a double-quoted
+/// string with `"`, `\` and control characters escaped.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Dotted {
+    hide_hops: bool,
+}
+
+impl Dotted {
+    #[must_use]
+    pub fn new() -> Self {
+        Dotted { hide_hops: false }
+    }
+
+    /// Leave out the `?` segments for `Some`, newtype structs and newtype
+    /// variants, so `servers.?.0.?.host` renders as `servers.0.host`.
+    #[must_use]
+    pub fn hide_hops(mut self) -> Self {
+        self.hide_hops = true;
+        self
+    }
+}
+
+impl PathFormatter for Dotted {
+    fn write_root(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(".")
+    }
+
+    fn write_segment(
+        &self,
+        formatter: &mut fmt::Formatter,
+        segment: Segment,
+        first: bool,
+    ) -> fmt::Result {
+        if !first {
+            formatter.write_str(".")?;
+        }
+        match segment {
+            Segment::Seq { index } => Display::fmt(&index, formatter),
+            Segment::Map { key: Key::Str(key) } => write_maybe_quoted(formatter, key),
+            Segment::Map { key } => write_maybe_quoted(formatter, &key.to_string()),
+            Segment::MapKey => formatter.write_str("?key"),
+            Segment::Variant { name } => write_maybe_quoted(formatter, name),
+            Segment::Some | Segment::NewtypeStruct | Segment::NewtypeVariant => {
+                formatter.write_str("?")
+            }
+        }
+    }
+
+    fn skip_segment(&self, segment: Segment) -> bool {
+        self.hide_hops && is_hop(segment)
+    }
+}
+
+/// Subscript style, like `servers[0]["example.com"]`.
+///
+/// A leading key that is valid as a bare key (see [`Dotted`]) is written
+/// without brackets. Hops for `Some`, newtype structs and newtype variants
+/// are written as `[?]` unless hidden, and the step into a map key is
+/// written as `[?key]`.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Brackets {
+    hide_hops: bool,
+}
+
+impl Brackets {
+    #[must_use]
+    pub fn new() -> Self {
+        Brackets { hide_hops: false }
+    }
+
+    /// Leave out the `[?]` segments for `Some`, newtype structs and newtype
+    /// variants.
+    #[must_use]
+    pub fn hide_hops(mut self) -> Self {
+        self.hide_hops = true;
+        self
+    }
+}
+
+impl PathFormatter for Brackets {
+    fn write_root(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(".")
+    }
+
+    fn write_segment(
+        &self,
+        formatter: &mut fmt::Formatter,
+        segment: Segment,
+        first: bool,
+    ) -> fmt::Result {
+        match segment {
+            Segment::Seq { index } => write!(formatter, "[{}]", index),
+            Segment::Map { key: Key::Str(key) } if first && is_bare_key(key) => {
+                formatter.write_str(key)
+            }
+            Segment::Variant { name } if first && is_bare_key(name) => formatter.write_str(name),
+            Segment::Map {
+                key: key @ (Key::Int(_) | Key::Uint(_) | Key::Bool(_)),
+            } => write!(formatter, "[{}]", key),
+            Segment::Map { key } => {
+                formatter.write_str("[")?;
+                match key {
+                    Key::Str(key) => write_quoted(formatter, key, '"')?,
+                    key => write_quoted(formatter, &key.to_string(), '"')?,
+                }
+                formatter.write_str("]")
+            }
+            Segment::Variant { name } => {
+                formatter.write_str("[")?;
+                write_quoted(formatter, name, '"')?;
+                formatter.write_str("]")
+            }
+            Segment::MapKey => formatter.write_str("[?key]"),
+            Segment::Some | Segment::NewtypeStruct | Segment::NewtypeVariant => {
+                formatter.write_str("[?]")
+            }
+        }
+    }
+
+    fn skip_segment(&self, segment: Segment) -> bool {
+        self.hide_hops && is_hop(segment)
+    }
+}
+
+/// Unambiguous dot-separated style that can be parsed back by
+/// [`PathBuf::from_lossless`].
+///
+/// Sequence indices are written as bare digits. String keys made up of only
+/// ASCII letters, digits, `_` and `-` are written bare unless they consist of
+/// only digits; any other string key, including the empty key and keys
+/// containing whitespace or `.`, is written as a double-quoted string. `Some`,
+/// newtype structs and newtype variants are written as `?`, `?newtype` and
+/// `?variant` respectively, and the step into a map key is written as `?key`.
+/// Enum variants are written as `@` followed by the name, quoted the same way
+/// as a string key. The root is written as `.`.
+///
+/// Keys that are not strings are written inside angle brackets: unsigned
+/// integers as `<2>`, signed integers with an explicit sign as `<+2>`, floats
+/// as `<2.0>`, and otherwise `<true>`, `<'c'>`, `<b"\xff">`, `<()>`, and
+/// `<"summary">` for keys of other kinds.
+///
+/// Two distinct paths never render to the same string, so the rendered form
+/// is suitable as a key for deduplicating reports.
+///
+/// ```
+/// # use serde_ignored::{Lossless, Path};
+/// #
+/// # let root = Path::Root;
+/// # let map = Path::Map { parent: &root, key: "a.b".into() };
+/// # let path = Path::Map { parent: &map, key: "2".into() };
+/// #
+/// assert_eq!(path.display_with(Lossless).to_string(), r#""a.b"."2""#);
+/// ```
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Lossless;
+
+impl PathFormatter for Lossless {
+    fn write_root(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(".")
+    }
+
+    fn write_segment(
+        &self,
+        formatter: &mut fmt::Formatter,
+        segment: Segment,
+        first: bool,
+    ) -> fmt::Result {
+        if !first {
+            formatter.write_str(".")?;
+        }
+        match segment {
+            Segment::Seq { index } => Display::fmt(&index, formatter),
+            Segment::Map { key } => write_lossless_key(formatter, key),
+            Segment::MapKey => formatter.write_str("?key"),
+            Segment::Variant { name } => {
+                formatter.write_str("@")?;
+                write_maybe_quoted(formatter, name)
+            }
+            Segment::Some => formatter.write_str("?"),
+            Segment::NewtypeStruct => formatter.write_str("?newtype"),
+            Segment::NewtypeVariant => formatter.write_str("?variant"),
+        }
+    }
+}
+
+impl PathBuf {
+    /// Parses a path in the format written by the [`Lossless`] style.
+    pub fn from_lossless(s: &str) -> Result<PathBuf, ParseError> {
+        let mut path = PathBuf::new();
+        if s == "." {
+            return Ok(path);
+        }
+
+        let mut parser = Parser { input: s, pos: 0 };
+        loop {
+            let segment = parser.segment()?;
+            path.push(segment);
+            match parser.next_byte() {
+                None => return Ok(path),
+                Some(b'.') => {}
+                Some(_) => return Err(parser.error("expected '.' between segments")),
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, msg: &'static str) -> ParseError {
+        ParseError::new(msg, self.pos)
+    }
+
+    fn next_byte(&mut self) -> Option<u8> {
+        let byte = *self.input.as_bytes().get(self.pos)?;
+        self.pos += 1;
+        Some(byte)
+    }
+
+    fn take_while(&mut self, f: fn(u8) -> bool) -> &'a str {
+        let start = self.pos;
+        let len = self.input.as_bytes()[start..]
+            .iter()
+            .take_while(|&&b| f(b))
+            .count();
+        self.pos += len;
+        &self.input[start..self.pos]
+    }
+
+    fn segment(&mut self) -> Result<SegmentBuf, ParseError> {
+        match self.input.as_bytes().get(self.pos) {
+            Some(b'"') => {
+                self.pos += 1;
+                let key = self.quoted('"')?;
+                Ok(SegmentBuf::Map { key: Key::Str(key) })
+            }
+            Some(b'<') => {
+                self.pos += 1;
+                let key = self.typed_key()?;
+                match self.next_byte() {
+                    Some(b'>') => Ok(SegmentBuf::Map { key }),
+                    _ => Err(ParseError::new("expected '>' after key", self.pos - 1)),
+                }
+            }
+            Some(b'?') => {
+                self.pos += 1;
+                let start = self.pos;
+                match self.take_while(|b| b.is_ascii_lowercase()) {
+                    "" => Ok(SegmentBuf::Some),
+                    "newtype" => Ok(SegmentBuf::NewtypeStruct),
+                    "variant" => Ok(SegmentBuf::NewtypeVariant),
+                    "key" => Ok(SegmentBuf::MapKey),
+                    _ => Err(ParseError::new("unrecognized '?' segment", start)),
+                }
+            }
+            Some(b'@') => {
+                self.pos += 1;
+                let name = if self.input[self.pos..].starts_with('"') {
+                    self.pos += 1;
+                    self.quoted('"')?
+                } else {
+                    String::from(self.take_while(is_bare_byte))
+                };
+                if name.is_empty() {
+                    return Err(self.error("expected variant name"));
+                }
+                Ok(SegmentBuf::Variant { name })
+            }
+            _ => {
+                let token = self.take_while(is_bare_byte);
+                if token.is_empty() {
+                    Err(self.error("expected segment"))
+                } else if is_index(token) {
+                    match token.parse() {
+                        Ok(index) => Ok(SegmentBuf::Seq { index }),
+                        Err(_) => Err(self.error("sequence index out of range")),
+                    }
+                } else {
+                    Ok(SegmentBuf::Map {
+                        key: Key::from(token),
+                    })
+                }
+            }
+        }
+    }
+
+    fn typed_key(&mut self) -> Result<Key, ParseError> {
+        let start = self.pos;
+        let rest = &self.input.as_bytes()[start..];
+        if rest.starts_with(b"\"") {
+            self.pos += 1;
+            return self.quoted('"').map(Key::Other);
+        }
+        if rest.starts_with(b"'") {
+            self.pos += 1;
+            let s = self.quoted('\'')?;
+            let mut chars = s.chars();
+            return match (chars.next(), chars.next()) {
+                (Some(ch), None) => Ok(Key::Char(ch)),
+                _ => Err(ParseError::new("expected exactly one char", start)),
+            };
+        }
+        if rest.starts_with(b"b\"") {
+            self.pos += 2;
+            return self.byte_string().map(Key::Bytes);
+        }
+        if rest.starts_with(b"()") {
+            self.pos += 2;
+            return Ok(Key::Unit);
+        }
+
+        let token = self.take_while(|b| b != b'>');
+        let error = || ParseError::new("unrecognized key", start);
+        match token {
+            "true" => Ok(Key::Bool(true)),
+            "false" => Ok(Key::Bool(false)),
+            _ if is_index(token) => token.parse().map(Key::Uint).map_err(|_| error()),
+            _ if token.starts_with(['+', '-']) && is_index(&token[1..]) => {
+                token.parse().map(Key::Int).map_err(|_| error())
+            }
+            _ => token.parse().map(Key::Float).map_err(|_| error()),
+        }
+    }
+
+    fn byte_string(&mut self) -> Result<Vec<u8>, ParseError> {
+        let mut bytes = Vec::new();
+        loop {
+            match self.next_byte() {
+                None => return Err(self.error("unterminated byte string")),
+                Some(b'"') => return Ok(bytes),
+                Some(b'\\') => match self.next_byte() {
+                    Some(b'"') => bytes.push(b'"'),
+                    Some(b'\\') => bytes.push(b'\\'),
+                    Some(b'x') => {
+                        let start = self.pos;
+                        let hex = self.input.get(start..start + 2);
+                        match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                            Some(byte) => bytes.push(byte),
+                            None => return Err(self.error("invalid byte escape")),
+                        }
+                        self.pos += 2;
+                    }
+                    _ => return Err(self.error("invalid escape in byte string")),
+                },
+                Some(byte) => bytes.push(byte),
+            }
+        }
+    }
+
+    fn quoted(&mut self, quote: char) -> Result<String, ParseError> {
+        let mut key = String::new();
+        loop {
+            let Some(ch) = self.input[self.pos..].chars().next() else {
+                return Err(self.error("unterminated quoted key"));
+            };
+            self.pos += ch.len_utf8();
+            match ch {
+                ch if ch == quote => return Ok(key),
+                '\\' => {
+                    let escape = match self.next_byte() {
+                        Some(b'"') => '"',
+                        Some(b'\'') => '\'',
+                        Some(b'\\') => '\\',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => self.unicode_escape()?,
+                        _ => return Err(self.error("invalid escape in quoted key")),
+                    };
+                    key.push(escape);
+                }
+                ch => key.push(ch),
+            }
+        }
+    }
+
+    fn unicode_escape(&mut self) -> Result<char, ParseError> {
+        let start = self.pos;
+        let hex = self.input.get(start..start + 4);
+        let code = hex.and_then(|hex| u32::from_str_radix(hex, 16).ok());
+        match code.and_then(char::from_u32) {
+            Some(ch) => {
+                self.pos += 4;
+                Ok(ch)
+            }
+            None => Err(self.error("invalid unicode escape in quoted key")),
+        }
+    }
+}
+
+/// Helper for rendering a path in a particular style using `{}`.
+///
+/// Returned by [`Path::display_with`] and [`PathBuf::display_with`].
+pub struct DisplayPath<'a, F> {
+    path: PathRef<'a>,
+    formatter: F,
+}
+
+#[derive(Copy, Clone)]
+enum PathRef<'a> {
+    Borrowed(&'a Path<'a>),
+    Owned(&'a PathBuf),
+}
+
+impl<'a> Path<'a> {
+    /// Renders this path in the style of the given formatter.
+    ///
+    /// ```
+    /// # use serde_ignored::{Dotted, Path};
+    /// #
+    /// # let root = Path::Root;
+    /// # let path = Path::Map { parent: &root, key: "example.com".into() };
+    /// #
+    /// assert_eq!(path.display_with(Dotted::new()).to_string(), "\"example.com\"");
+    /// ```
+    pub fn display_with<F>(&self, formatter: F) -> DisplayPath<'_, F>
+    where
+        F: PathFormatter,
+    {
+        DisplayPath {
+            path: PathRef::Borrowed(self),
+            formatter,
+        }
+    }
+}
+
+impl PathBuf {
+    /// Renders this path in the style of the given formatter.
+    pub fn display_with<F>(&self, formatter: F) -> DisplayPath<'_, F>
+    where
+        F: PathFormatter,
+    {
+        DisplayPath {
+            path: PathRef::Owned(self),
+            formatter,
+        }
+    }
+}
+
+impl<'a, F> Display for DisplayPath<'a, F>
+where
+    F: PathFormatter,
+{
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self.path {
+            PathRef::Borrowed(path) => write_path(&self.formatter, formatter, path.segments()),
+            PathRef::Owned(path) => write_path(&self.formatter, formatter, path.iter()),
+        }
+    }
+}
+
+fn write_path<'a, F, I>(style: &F, formatter: &mut fmt::Formatter, segments: I) -> fmt::Result
+where
+    F: PathFormatter,
+    I: Iterator<Item = Segment<'a>>,
+{
+    let mut first = true;
+    for segment in segments {
+        if style.skip_segment(segment) {
+            continue;
+        }
+        style.write_segment(formatter, segment, first)?;
+        first = false;
+    }
+    if first {
+        style.write_root(formatter)?;
+    }
+    Ok(())
+}
+
+fn is_hop(segment: Segment) -> bool {
+    match segment {
+        Segment::Some | Segment::NewtypeStruct | Segment::NewtypeVariant => true,
+        Segment::Seq { .. } | Segment::Map { .. } | Segment::MapKey | Segment::Variant { .. } => {
+            false
+        }
+    }
+}
+
+fn is_bare_key(key: &str) -> bool {
+    !key.is_empty() && key.bytes().all(is_bare_byte)
+}
+
+fn is_bare_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'-'
+}
+
+fn is_index(key: &str) -> bool {
+    !key.is_empty() && key.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn write_maybe_quoted(formatter: &mut fmt::Formatter, key: &str) -> fmt::Result {
+    if is_bare_key(key) {
+        formatter.write_str(key)
+    } else {
+        write_quoted(formatter, key, '"')
+    }
+}
+
+fn write_lossless_key(formatter: &mut fmt::Formatter, key: &Key) -> fmt::Result {
+    match key {
+        Key::Str(key) if is_bare_key(key) && !is_index(key) => formatter.write_str(key),
+        Key::Str(key) => write_quoted(formatter, key, '"'),
+        Key::Int(i) => write!(formatter, "<{:+}>", i),
+        Key::Uint(u) => write!(formatter, "<{}>", u),
+        Key::Bool(b) => write!(formatter, "<{}>", b),
+        Key::Char(c) => {
+            formatter.write_str("<")?;
+            write_quoted(formatter, c.encode_utf8(&mut [0; 4]), '\'')?;
+            formatter.write_str(">")
+        }
+        Key::Float(f) => write!(formatter, "<{:?}>", f),
+        Key::Bytes(bytes) => {
+            formatter.write_str("<b\"")?;
+            for &byte in bytes {
+                match byte {
+                    b'"' | b'\\' => write!(formatter, "\\{}", byte as char)?,
+                    b' '..=b'~' => formatter.write_char(byte as char)?,
+                    _ => write!(formatter, "\\x{:02x}", byte)?,
+                }
+            }
+            formatter.write_str("\">")
+        }
+        Key::Unit => formatter.write_str("<()>"),
+        Key::Other(summary) => {
+            formatter.write_str("<")?;
+            write_quoted(formatter, summary, '"')?;
+            formatter.write_str(">")
+        }
+    }
+}
+
+fn write_quoted(formatter: &mut fmt::Formatter, key: &str, quote: char) -> fmt::Result {
+    formatter.write_char(quote)?;
+    for ch in key.chars() {
+        match ch {
+            ch if ch == quote => write!(formatter, "\\{}", ch)?,
+            '\\' => formatter.write_str("\\\\")?,
+            '\n' => formatter.write_str("\\n")?,
+            '\r' => formatter.write_str("\\r")?,
+            '\t' => formatter.write_str("\\t")?,
+            ch if ch.is_control() => write!(formatter, "\\u{:04X}", ch as u32)?,
+            ch => formatter.write_char(ch)?,
+        }
+    }
+    formatter.write_char(quote)
+}
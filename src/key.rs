@@ -0,0 +1,216 @@
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt::{self, Display, Write as _};
+use core::hash::{Hash, Hasher};
+use serde::ser::{Serialize, Serializer};
+
+/// Map key in a [`Path`](crate::Path), preserving the kind of value the
+/// input used for it.
+///
+/// Integer keys are `Int` or `Uint` depending on whether the input reported
+/// them through a signed or unsigned visitor method. They are compared by
+/// value, so that `Int(2)` and `Uint(2)` are the same key, as a map with keys
+/// of type `i32` may be read through one and written through the other.
+/// `Str("2")` is a different key even though it also displays as `2`.
+#[derive(Clone, Debug)]
+pub enum Key {
+    Str(String),
+    Int(i128),
+    Uint(u128),
+    Bool(bool),
+    Char(char),
+    /// Compared and hashed by bit pattern (via `f64::total_cmp`), so that
+    /// every key is equal to itself, including NaN.
+    Float(f64),
+    Bytes(Vec<u8>),
+    Unit,
+    /// Key of some other kind, such as a sequence or map, summarized as a
+    /// string.
+    Other(String),
+}
+
+impl Key {
+    /// The key as a string slice, if it is a `Str` key.
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Key::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn discriminant(&self) -> u8 {
+        match self {
+            Key::Str(_) => 0,
+            Key::Int(_) => 1,
+            Key::Uint(_) => 2,
+            Key::Bool(_) => 3,
+            Key::Char(_) => 4,
+            Key::Float(_) => 5,
+            Key::Bytes(_) => 6,
+            Key::Unit => 7,
+            Key::Other(_) => 8,
+        }
+    }
+}
+
+impl From<&str> for Key {
+    fn from(s: &str) -> Self {
+        Key::Str(s.to_owned())
+    }
+}
+
+impl From<String> for Key {
+    fn from(s: String) -> Self {
+        Key::Str(s)
+    }
+}
+
+/// Renders integer, bool and string keys the same way they were rendered
+/// before keys were typed, so `Display for Path` is unchanged for them.
+impl Display for Key {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Key::Str(s) | Key::Other(s) => formatter.write_str(s),
+            Key::Int(i) => Display::fmt(i, formatter),
+            Key::Uint(u) => Display::fmt(u, formatter),
+            Key::Bool(b) => Display::fmt(b, formatter),
+            Key::Char(c) => Display::fmt(c, formatter),
+            Key::Float(f) => Display::fmt(f, formatter),
+            Key::Bytes(bytes) => {
+                if let Ok(s) = core::str::from_utf8(bytes) {
+                    return formatter.write_str(s);
+                }
+                for byte in bytes {
+                    write!(formatter, "{:02x}", byte)?;
+                }
+                Ok(())
+            }
+            Key::Unit => formatter.write_str("()"),
+        }
+    }
+}
+
+/// Serializes the key as the kind of value it was captured from. `Other`
+/// keys are serialized as their summary string.
+impl Serialize for Key {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Key::Str(s) | Key::Other(s) => serializer.serialize_str(s),
+            Key::Int(i) => match i64::try_from(*i) {
+                Ok(i) => serializer.serialize_i64(i),
+                Err(_) => serializer.serialize_i128(*i),
+            },
+            Key::Uint(u) => match u64::try_from(*u) {
+                Ok(u) => serializer.serialize_u64(u),
+                Err(_) => serializer.serialize_u128(*u),
+            },
+            Key::Bool(b) => serializer.serialize_bool(*b),
+            Key::Char(c) => serializer.serialize_char(*c),
+            Key::Float(f) => serializer.serialize_f64(*f),
+            Key::Bytes(bytes) => serializer.serialize_bytes(bytes),
+            Key::Unit => serializer.serialize_unit(),
+        }
+    }
+}
+
+impl PartialEq for Key {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Key {}
+
+impl PartialOrd for Key {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Key {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Key::Str(a), Key::Str(b)) | (Key::Other(a), Key::Other(b)) => a.cmp(b),
+            (Key::Int(a), Key::Int(b)) => a.cmp(b),
+            (Key::Uint(a), Key::Uint(b)) => a.cmp(b),
+            (Key::Int(a), Key::Uint(b)) => cmp_int_uint(*a, *b),
+            (Key::Uint(a), Key::Int(b)) => cmp_int_uint(*b, *a).reverse(),
+            (Key::Bool(a), Key::Bool(b)) => a.cmp(b),
+            (Key::Char(a), Key::Char(b)) => a.cmp(b),
+            (Key::Float(a), Key::Float(b)) => a.total_cmp(b),
+            (Key::Bytes(a), Key::Bytes(b)) => a.cmp(b),
+            _ => self.discriminant().cmp(&other.discriminant()),
+        }
+    }
+}
+
+fn cmp_int_uint(int: i128, uint: u128) -> Ordering {
+    match u128::try_from(int) {
+        Ok(int) => int.cmp(&uint),
+        Err(_) => Ordering::Less,
+    }
+}
+
+impl Hash for Key {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        if let Key::Int(i) = *self {
+            if let Ok(u) = u128::try_from(i) {
+                // Equal to the Uint key with the same value.
+                return Key::Uint(u).hash(state);
+            }
+        }
+        self.discriminant().hash(state);
+        match self {
+            Key::Str(s) | Key::Other(s) => s.hash(state),
+            Key::Int(i) => i.hash(state),
+            Key::Uint(u) => u.hash(state),
+            Key::Bool(b) => b.hash(state),
+            Key::Char(c) => c.hash(state),
+            Key::Float(f) => f.to_bits().hash(state),
+            Key::Bytes(bytes) => bytes.hash(state),
+            Key::Unit => {}
+        }
+    }
+}
+
+pub(crate) fn summarize_seq(elements: &[Option<Key>]) -> Key {
+    let mut summary = String::from("[");
+    for (i, element) in elements.iter().enumerate() {
+        if i > 0 {
+            summary.push_str(", ");
+        }
+        push_summary(&mut summary, element.as_ref());
+    }
+    summary.push(']');
+    Key::Other(summary)
+}
+
+pub(crate) fn summarize_map(entries: &[(Option<Key>, Option<Key>)]) -> Key {
+    let mut summary = String::from("{");
+    for (i, (key, value)) in entries.iter().enumerate() {
+        if i > 0 {
+            summary.push(',');
+        }
+        summary.push(' ');
+        push_summary(&mut summary, key.as_ref());
+        summary.push_str(": ");
+        push_summary(&mut summary, value.as_ref());
+    }
+    summary.push_str(if entries.is_empty() { "}" } else { " }" });
+    Key::Other(summary)
+}
+
+fn push_summary(summary: &mut String, key: Option<&Key>) {
+    match key {
+        Some(key) => {
+            let _ = write!(summary, "{}", key);
+        }
+        None => summary.push('_'),
+    }
+}
@@ -0,0 +1,165 @@
+use crate::value::Value;
+use core::fmt;
+use serde::de::{
+    Deserialize, Deserializer, EnumAccess, IgnoredAny, MapAccess, SeqAccess, VariantAccess, Visitor,
+};
+
+/// What kind of value was ignored, as reported by [`Context::kind`].
+///
+/// [`Context::kind`]: crate::Context::kind
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// Unit, or an absent optional value such as JSON `null`.
+    Null,
+    /// Boolean, number or char.
+    Scalar,
+    /// String or byte string.
+    String,
+    Seq,
+    /// Map, struct or enum.
+    Map,
+}
+
+/// Kind of a value, and how many entries it has if it is a container.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Shape {
+    pub kind: Kind,
+    pub len: Option<usize>,
+}
+
+impl Shape {
+    fn scalar(kind: Kind) -> Self {
+        Shape { kind, len: None }
+    }
+
+    fn container(kind: Kind, len: usize) -> Self {
+        Shape {
+            kind,
+            len: Some(len),
+        }
+    }
+
+    pub fn of(value: &Value) -> Self {
+        match value {
+            Value::Unit | Value::None => Shape::scalar(Kind::Null),
+            Value::Bool(_) | Value::Int(_) | Value::Uint(_) | Value::Float(_) | Value::Char(_) => {
+                Shape::scalar(Kind::Scalar)
+            }
+            Value::Str(_) | Value::Bytes(_) => Shape::scalar(Kind::String),
+            Value::Some(v) | Value::Newtype(v) => Shape::of(v),
+            Value::Seq(elements) => Shape::container(Kind::Seq, elements.len()),
+            Value::Map(entries) => Shape::container(Kind::Map, entries.len()),
+        }
+    }
+}
+
+/// Consumes the value, counting the entries of containers but skipping their
+/// content.
+impl<'de> Deserialize<'de> for Shape {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(ShapeVisitor)
+    }
+}
+
+struct ShapeVisitor;
+
+impl<'de> Visitor<'de> for ShapeVisitor {
+    type Value = Shape;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any value")
+    }
+
+    fn visit_bool<E>(self, _v: bool) -> Result<Shape, E> {
+        Ok(Shape::scalar(Kind::Scalar))
+    }
+
+    fn visit_i64<E>(self, _v: i64) -> Result<Shape, E> {
+        Ok(Shape::scalar(Kind::Scalar))
+    }
+
+    fn visit_i128<E>(self, _v: i128) -> Result<Shape, E> {
+        Ok(Shape::scalar(Kind::Scalar))
+    }
+
+    fn visit_u64<E>(self, _v: u64) -> Result<Shape, E> {
+        Ok(Shape::scalar(Kind::Scalar))
+    }
+
+    fn visit_u128<E>(self, _v: u128) -> Result<Shape, E> {
+        Ok(Shape::scalar(Kind::Scalar))
+    }
+
+    fn visit_f64<E>(self, _v: f64) -> Result<Shape, E> {
+        Ok(Shape::scalar(Kind::Scalar))
+    }
+
+    fn visit_char<E>(self, _v: char) -> Result<Shape, E> {
+        Ok(Shape::scalar(Kind::Scalar))
+    }
+
+    fn visit_str<E>(self, _v: &str) -> Result<Shape, E> {
+        Ok(Shape::scalar(Kind::String))
+    }
+
+    fn visit_bytes<E>(self, _v: &[u8]) -> Result<Shape, E> {
+        Ok(Shape::scalar(Kind::String))
+    }
+
+    fn visit_unit<E>(self) -> Result<Shape, E> {
+        Ok(Shape::scalar(Kind::Null))
+    }
+
+    fn visit_none<E>(self) -> Result<Shape, E> {
+        Ok(Shape::scalar(Kind::Null))
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Shape, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Shape::deserialize(deserializer)
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Shape, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Shape::deserialize(deserializer)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Shape, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut len = 0;
+        while let Some(IgnoredAny) = seq.next_element()? {
+            len += 1;
+        }
+        Ok(Shape::container(Kind::Seq, len))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Shape, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut len = 0;
+        while let Some((IgnoredAny, IgnoredAny)) = map.next_entry()? {
+            len += 1;
+        }
+        Ok(Shape::container(Kind::Map, len))
+    }
+
+    // Same as Value, which records an enum as a map with a single entry.
+    fn visit_enum<A>(self, data: A) -> Result<Shape, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        let (IgnoredAny, content) = data.variant()?;
+        content.newtype_variant::<IgnoredAny>()?;
+        Ok(Shape::container(Kind::Map, 1))
+    }
+}
@@ -97,12 +97,55 @@
 extern crate alloc;
 extern crate serde_core as serde;
 
+mod allow;
+mod callback;
+mod error;
+mod extras;
+mod flatten;
+mod format;
+mod key;
+mod kind;
+mod path;
+mod pattern;
+mod pointer;
+mod replay;
+pub mod ser;
+mod suggest;
+mod tagged;
+mod value;
+
 use alloc::borrow::ToOwned;
+use alloc::collections::BTreeSet;
 use alloc::string::{String, ToString};
+use alloc::vec;
 use alloc::vec::Vec;
-use core::fmt::{self, Display};
+use core::cell::Cell;
+use core::fmt;
 use serde::de::{self, Deserialize, DeserializeSeed, Visitor};
 
+use crate::allow::Content;
+use crate::callback::{
+    is_expected, position, Fallible, Inspecting, WithContext, WithDuplicates, WithMissing,
+    WithValue,
+};
+use crate::flatten::Sides;
+use crate::kind::Shape;
+use crate::tagged::Attempts;
+
+pub use crate::allow::{Allow, Intentional};
+pub use crate::callback::{Callback, Context, Reason};
+pub use crate::error::{ParseError, StrictError};
+pub use crate::extras::Extras;
+pub use crate::flatten::Flatten;
+pub use crate::format::{Brackets, DisplayPath, Dotted, Lossless, PathFormatter};
+pub use crate::key::Key;
+pub use crate::kind::Kind;
+pub use crate::path::{Ancestors, Path, PathBuf, Segment, SegmentBuf, Segments};
+pub use crate::pattern::PathPattern;
+pub use crate::ser::Serializer;
+pub use crate::tagged::{Tagged, TaggedEnum, Tagging};
+pub use crate::value::{Value, ValueDeserializer};
+
 /// Entry point. See crate documentation for an example.
 pub fn deserialize<'de, D, F, T>(deserializer: D, mut callback: F) -> Result<T, D::Error>
 where
@@ -113,12 +156,297 @@ where
     T::deserialize(Deserializer::new(deserializer, &mut callback))
 }
 
+/// Entry point for a callback that also receives the [`Context`] in which each
+/// value was ignored.
+///
+/// ```
+/// # use serde_derive::Deserialize;
+/// #
+/// # #[derive(Deserialize)]
+/// # struct Dependency {
+/// #     version: String,
+/// # }
+/// #
+/// # let j = r#"{"version": "1.0", "typo1": ""}"#;
+/// # let jd = &mut serde_json::Deserializer::from_str(j);
+/// let mut warnings = Vec::new();
+///
+/// let _: Dependency = serde_ignored::deserialize_with_context(jd, |path, context| {
+///     let container = context.container().unwrap_or("map");
+///     warnings.push(format!("unknown field `{}` in {}", path, container));
+/// })?;
+///
+/// assert_eq!(warnings, ["unknown field `typo1` in Dependency"]);
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+pub fn deserialize_with_context<'de, D, F, T>(deserializer: D, callback: F) -> Result<T, D::Error>
+where
+    D: de::Deserializer<'de>,
+    F: FnMut(Path, &Context),
+    T: Deserialize<'de>,
+{
+    let mut callback = WithContext(callback);
+    T::deserialize(Deserializer::with_callback(deserializer, &mut callback))
+}
+
+/// Entry point for a callback that receives the [`Context`] of each ignored
+/// value, including its [kind] and [size].
+///
+/// Unlike [`deserialize_with_context`], this reads through every ignored value
+/// instead of letting the input skip it, which is slower and requires a
+/// self-describing format.
+///
+/// ```
+/// # use serde_derive::Deserialize;
+/// #
+/// # #[derive(Deserialize)]
+/// # struct Dependency {
+/// #     version: String,
+/// # }
+/// #
+/// use serde_ignored::Kind;
+///
+/// let j = r#"{"version": "1.0", "features": ["std", "derive"]}"#;
+/// let jd = &mut serde_json::Deserializer::from_str(j);
+/// let mut ignored = Vec::new();
+///
+/// let _: Dependency = serde_ignored::deserialize_inspecting(jd, |path, context| {
+///     ignored.push((path.to_string(), context.kind(), context.size()));
+/// })?;
+///
+/// assert_eq!(ignored, [("features".to_owned(), Some(Kind::Seq), Some(2))]);
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+///
+/// [kind]: Context::kind
+/// [size]: Context::size
+pub fn deserialize_inspecting<'de, D, F, T>(deserializer: D, callback: F) -> Result<T, D::Error>
+where
+    D: de::Deserializer<'de>,
+    F: FnMut(Path, &Context),
+    T: Deserialize<'de>,
+{
+    let mut callback = Inspecting(callback);
+    T::deserialize(Deserializer::with_callback(deserializer, &mut callback))
+}
+
+/// Entry point for a callback that can reject an ignored value, for example to
+/// deny unknown fields in types that do not have
+/// `#[serde(deny_unknown_fields)]`.
+///
+/// Deserialization stops at the first ignored value for which the callback
+/// returns an error. The error is reported as a custom error of the
+/// deserializer, with the path in front of the callback's message.
+///
+/// ```
+/// # use serde_derive::Deserialize;
+/// #
+/// # #[derive(Debug, Deserialize)]
+/// # struct Dependency {
+/// #     version: String,
+/// # }
+/// #
+/// let j = r#"{"version": "1.0", "typo1": ""}"#;
+/// let jd = &mut serde_json::Deserializer::from_str(j);
+///
+/// let result: Result<Dependency, _> =
+///     serde_ignored::deserialize_fallible(jd, |_path| Err("unknown field"));
+///
+/// let err = result.unwrap_err();
+/// assert_eq!(err.to_string(), "typo1: unknown field at line 1 column 28");
+/// ```
+pub fn deserialize_fallible<'de, D, F, T, E>(deserializer: D, callback: F) -> Result<T, D::Error>
+where
+    D: de::Deserializer<'de>,
+    F: FnMut(&Path) -> Result<(), E>,
+    E: fmt::Display,
+    T: Deserialize<'de>,
+{
+    let mut callback = Fallible(callback);
+    T::deserialize(Deserializer::with_callback(deserializer, &mut callback))
+}
+
+/// Entry point for a callback that also receives a copy of each ignored value.
+///
+/// The copy is read with `deserialize_any`, so this requires a
+/// self-describing format. With one such as bincode or postcard, which
+/// cannot say what kind of value comes next, an ignored value is an error
+/// even where [`deserialize`] would have let the input skip it.
+///
+/// ```
+/// # use serde_derive::Deserialize;
+/// #
+/// # #[derive(Deserialize)]
+/// # struct Config {
+/// #     timeout: u64,
+/// # }
+/// #
+/// let j = r#"{"timeout": 5, "timeout_ms": 500}"#;
+/// let jd = &mut serde_json::Deserializer::from_str(j);
+/// let mut warnings = Vec::new();
+///
+/// let _: Config = serde_ignored::deserialize_with_values(jd, |path, value| {
+///     warnings.push(format!("ignored `{} = {}`", path, value));
+/// })?;
+///
+/// assert_eq!(warnings, ["ignored `timeout_ms = 500`"]);
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+pub fn deserialize_with_values<'de, D, F, T>(deserializer: D, callback: F) -> Result<T, D::Error>
+where
+    D: de::Deserializer<'de>,
+    F: FnMut(Path, Value),
+    T: Deserialize<'de>,
+{
+    let mut callback = WithValue(callback);
+    T::deserialize(Deserializer::with_callback(deserializer, &mut callback))
+}
+
+/// Entry point with a second callback for struct fields that are absent from
+/// the input, and so were filled in by `#[serde(default)]` or left as `None`.
+///
+/// ```
+/// # use serde_derive::Deserialize;
+/// #
+/// #[derive(Deserialize)]
+/// struct Dependency {
+///     version: String,
+///     #[serde(default)]
+///     optional: bool,
+/// }
+///
+/// let j = r#"{"version": "1.0", "typo1": ""}"#;
+/// let jd = &mut serde_json::Deserializer::from_str(j);
+/// let mut ignored = Vec::new();
+/// let mut missing = Vec::new();
+///
+/// let _: Dependency = serde_ignored::deserialize_with_missing(
+///     jd,
+///     |path| ignored.push(path.to_string()),
+///     |path| missing.push(path.to_string()),
+/// )?;
+///
+/// assert_eq!(ignored, ["typo1"]);
+/// assert_eq!(missing, ["optional"]);
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+///
+/// Fields are taken from the list that the struct passes to
+/// `deserialize_struct`. For a struct derived with `#[serde(alias = "...")]`
+/// that list contains the aliases too, without saying which names belong to
+/// the same field, so every name not used in the input is reported as
+/// missing. A field given under its alias has its own name reported, and one
+/// given under its own name has each of its aliases reported.
+pub fn deserialize_with_missing<'de, D, F, G, T>(
+    deserializer: D,
+    ignored: F,
+    missing: G,
+) -> Result<T, D::Error>
+where
+    D: de::Deserializer<'de>,
+    F: FnMut(Path),
+    G: FnMut(Path),
+    T: Deserialize<'de>,
+{
+    let mut callback = WithMissing(ignored, missing);
+    T::deserialize(Deserializer::with_callback(deserializer, &mut callback))
+}
+
+/// Entry point with a second callback for map entries that are shadowed by a
+/// later entry with the same key.
+///
+/// ```
+/// # use std::collections::HashMap;
+/// #
+/// let j = r#"{"serde": "1.0", "rand": "0.8", "serde": "1.0.100"}"#;
+/// let jd = &mut serde_json::Deserializer::from_str(j);
+/// let mut duplicates = Vec::new();
+///
+/// let deps: HashMap<String, String> = serde_ignored::deserialize_with_duplicates(
+///     jd,
+///     |_path| {},
+///     |path| duplicates.push(path.to_string()),
+/// )?;
+///
+/// assert_eq!(deps["serde"], "1.0.100");
+/// assert_eq!(duplicates, ["serde"]);
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+pub fn deserialize_with_duplicates<'de, D, F, G, T>(
+    deserializer: D,
+    ignored: F,
+    duplicate: G,
+) -> Result<T, D::Error>
+where
+    D: de::Deserializer<'de>,
+    F: FnMut(Path),
+    G: FnMut(Path),
+    T: Deserialize<'de>,
+{
+    let mut callback = WithDuplicates(ignored, duplicate);
+    T::deserialize(Deserializer::with_callback(deserializer, &mut callback))
+}
+
+/// Entry point that keeps every ignored value, so that it can be written back
+/// out with [`Serializer`].
+///
+/// This includes the values ignored inside of an [`Allow`], which are not
+/// reported by any of the other entry points. Values are read with
+/// `deserialize_any`, so this requires a self-describing format.
+pub fn deserialize_preserving<'de, D, T>(deserializer: D) -> Result<(T, Extras), D::Error>
+where
+    D: de::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    let mut extras = Extras::new();
+    let value = T::deserialize(Deserializer::with_callback(deserializer, &mut extras))?;
+    Ok((value, extras))
+}
+
+/// Entry point that treats any ignored value as an error, after first
+/// deserializing the whole input so that all of them are reported together.
+///
+/// ```
+/// # use serde_derive::Deserialize;
+/// #
+/// # #[derive(Debug, Deserialize)]
+/// # struct Dependency {
+/// #     version: String,
+/// # }
+/// #
+/// let j = r#"{"version": "1.0", "typo1": "", "typo2": ""}"#;
+/// let jd = &mut serde_json::Deserializer::from_str(j);
+///
+/// let result: Result<Dependency, _> = serde_ignored::deserialize_strict(jd);
+///
+/// let err = result.unwrap_err();
+/// assert_eq!(err.to_string(), "2 unknown keys: `typo1`, `typo2`");
+/// assert_eq!(err.ignored().len(), 2);
+/// ```
+pub fn deserialize_strict<'de, D, T>(deserializer: D) -> Result<T, StrictError<D::Error>>
+where
+    D: de::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    let mut ignored = Vec::new();
+    let value = deserialize(deserializer, |path| ignored.push(path.to_owned()))
+        .map_err(StrictError::Deserialize)?;
+    if ignored.is_empty() {
+        Ok(value)
+    } else {
+        Err(StrictError::Ignored(ignored))
+    }
+}
+
 /// Deserializer adapter that invokes a callback with the path to every unused
 /// field of the input.
 pub struct Deserializer<'a, 'b, D, F: 'b> {
     de: D,
     callback: &'b mut F,
     path: Path<'a>,
+    context: Context<'static>,
+    // Set when the value is skipped, for the sequence whose element this is.
+    skipped: Option<&'a Cell<bool>>,
 }
 
 impl<'a, 'b, D, F> Deserializer<'a, 'b, D, F>
@@ -135,44 +463,23 @@ where
     //
     // https://github.com/dtolnay/serde-ignored/pull/1
     pub fn new(de: D, callback: &'b mut F) -> Self {
+        Deserializer::with_callback(de, callback)
+    }
+}
+
+impl<'a, 'b, D, F> Deserializer<'a, 'b, D, F>
+where
+    F: Callback,
+{
+    /// Like `new`, but for any implementation of [`Callback`] rather than only
+    /// closures taking a `Path`.
+    pub fn with_callback(de: D, callback: &'b mut F) -> Self {
         Deserializer {
             de,
             callback,
             path: Path::Root,
-        }
-    }
-}
-
-/// Path to the current value in the input, like `dependencies.serde.typo1`.
-pub enum Path<'a> {
-    Root,
-    Seq { parent: &'a Path<'a>, index: usize },
-    Map { parent: &'a Path<'a>, key: String },
-    Some { parent: &'a Path<'a> },
-    NewtypeStruct { parent: &'a Path<'a> },
-    NewtypeVariant { parent: &'a Path<'a> },
-}
-
-impl<'a> Display for Path<'a> {
-    fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        struct Parent<'a>(&'a Path<'a>);
-
-        impl<'a> Display for Parent<'a> {
-            fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-                match *self.0 {
-                    Path::Root => Ok(()),
-                    ref path => write!(formatter, "{}.", path),
-                }
-            }
-        }
-
-        match *self {
-            Path::Root => formatter.write_str("."),
-            Path::Seq { parent, index } => write!(formatter, "{}{}", Parent(parent), index),
-            Path::Map { parent, ref key } => write!(formatter, "{}{}", Parent(parent), key),
-            Path::Some { parent }
-            | Path::NewtypeStruct { parent }
-            | Path::NewtypeVariant { parent } => write!(formatter, "{}?", Parent(parent)),
+            context: Context::default(),
+            skipped: None,
         }
     }
 }
@@ -182,7 +489,7 @@ impl<'a> Display for Path<'a> {
 impl<'a, 'b, 'de, D, F> de::Deserializer<'de> for Deserializer<'a, 'b, D, F>
 where
     D: de::Deserializer<'de>,
-    F: FnMut(Path),
+    F: Callback,
 {
     type Error = D::Error;
 
@@ -362,8 +669,10 @@ where
     where
         V: Visitor<'de>,
     {
-        self.de
-            .deserialize_unit_struct(name, Wrap::new(visitor, self.callback, &self.path))
+        self.de.deserialize_unit_struct(
+            name,
+            Wrap::new(visitor, self.callback, &self.path).with_container(name),
+        )
     }
 
     fn deserialize_newtype_struct<V>(
@@ -374,8 +683,30 @@ where
     where
         V: Visitor<'de>,
     {
-        self.de
-            .deserialize_newtype_struct(name, Wrap::new(visitor, self.callback, &self.path))
+        if name == allow::NAME {
+            if self.callback.captures_values() && self.callback.captures_allowed() {
+                let value = Value::deserialize(self.de)?;
+                let content = Content::new(value, self.callback, self.path, self.context);
+                return visitor.visit_seq(content);
+            }
+            // The visitor sees the underlying deserializer, so nothing inside
+            // of it gets reported.
+            return self.de.deserialize_newtype_struct(name, visitor);
+        }
+        if name == flatten::NAME {
+            let value = Value::deserialize(self.de)?;
+            let sides = Sides::new(value, self.callback, self.path, self.context);
+            return visitor.visit_seq(sides);
+        }
+        if name == tagged::NAME {
+            let value = Value::deserialize(self.de)?;
+            let attempts = Attempts::new(value, self.callback, self.path, self.context);
+            return visitor.visit_seq(attempts);
+        }
+        self.de.deserialize_newtype_struct(
+            name,
+            Wrap::new(visitor, self.callback, &self.path).with_container(name),
+        )
     }
 
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, D::Error>
@@ -390,8 +721,10 @@ where
     where
         V: Visitor<'de>,
     {
-        self.de
-            .deserialize_tuple(len, Wrap::new(visitor, self.callback, &self.path))
+        self.de.deserialize_tuple(
+            len,
+            Wrap::new(visitor, self.callback, &self.path).with_len(len),
+        )
     }
 
     fn deserialize_tuple_struct<V>(
@@ -403,8 +736,13 @@ where
     where
         V: Visitor<'de>,
     {
-        self.de
-            .deserialize_tuple_struct(name, len, Wrap::new(visitor, self.callback, &self.path))
+        self.de.deserialize_tuple_struct(
+            name,
+            len,
+            Wrap::new(visitor, self.callback, &self.path)
+                .with_container(name)
+                .with_len(len),
+        )
     }
 
     fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, D::Error>
@@ -424,8 +762,13 @@ where
     where
         V: Visitor<'de>,
     {
-        self.de
-            .deserialize_struct(name, fields, Wrap::new(visitor, self.callback, &self.path))
+        self.de.deserialize_struct(
+            name,
+            fields,
+            Wrap::new(visitor, self.callback, &self.path)
+                .with_container(name)
+                .with_fields(fields),
+        )
     }
 
     fn deserialize_enum<V>(
@@ -440,7 +783,9 @@ where
         self.de.deserialize_enum(
             name,
             variants,
-            Wrap::new(visitor, self.callback, &self.path),
+            Wrap::new(visitor, self.callback, &self.path)
+                .with_container(name)
+                .with_variants(variants),
         )
     }
 
@@ -448,7 +793,29 @@ where
     where
         V: Visitor<'de>,
     {
-        (self.callback)(self.path);
+        if let Some(skipped) = self.skipped {
+            skipped.set(true);
+        }
+        let reason = Reason::of(&self.path, &self.context);
+        let context = self.context.with_reason(reason);
+        if self.callback.captures_values() {
+            let value = Value::deserialize(self.de)?;
+            let context = context.with_shape(Shape::of(&value));
+            self.callback.ignored_value(self.path, &context, value)?;
+            // The visitor has no use for the value, as with serde_json's
+            // Value::deserialize_ignored_any.
+            return visitor.visit_unit();
+        }
+        if self.callback.inspects_values() {
+            // Not the input's deserialize_ignored_any, which may skip the value
+            // without telling the visitor anything about it, as serde_json's
+            // does.
+            let shape = Shape::deserialize(self.de)?;
+            let context = context.with_shape(shape);
+            self.callback.ignored(self.path, &context)?;
+            return visitor.visit_unit();
+        }
+        self.callback.ignored(self.path, &context)?;
         self.de.deserialize_ignored_any(visitor)
     }
 
@@ -465,12 +832,18 @@ where
     }
 }
 
-/// Wrapper that attaches context to a `Visitor`, `SeqAccess`, `EnumAccess` or
-/// `VariantAccess`.
+/// Wrapper that attaches context to a `Visitor` or `EnumAccess`.
 struct Wrap<'a, 'b, X, F: 'b> {
     delegate: X,
     callback: &'b mut F,
     path: &'a Path<'a>,
+    context: Context<'static>,
+    // Variant names passed to deserialize_enum, for formats that identify the
+    // variant by its index and to tell when a #[serde(other)] variant stood in
+    // for an unknown one.
+    variants: &'static [&'static str],
+    // Length of the tuple, past which elements are trailing data.
+    len: Option<usize>,
 }
 
 impl<'a, 'b, X, F> Wrap<'a, 'b, X, F> {
@@ -479,6 +852,34 @@ impl<'a, 'b, X, F> Wrap<'a, 'b, X, F> {
             delegate,
             callback,
             path,
+            context: Context::default(),
+            variants: &[],
+            len: None,
+        }
+    }
+
+    fn with_container(self, container: &'static str) -> Self {
+        let context = self.context.with_container(container);
+        Wrap { context, ..self }
+    }
+
+    fn with_fields(self, fields: &'static [&'static str]) -> Self {
+        let context = self.context.with_fields(fields);
+        Wrap { context, ..self }
+    }
+
+    fn with_context(self, context: Context<'static>) -> Self {
+        Wrap { context, ..self }
+    }
+
+    fn with_variants(self, variants: &'static [&'static str]) -> Self {
+        Wrap { variants, ..self }
+    }
+
+    fn with_len(self, len: usize) -> Self {
+        Wrap {
+            len: Some(len),
+            ..self
         }
     }
 }
@@ -487,7 +888,7 @@ impl<'a, 'b, X, F> Wrap<'a, 'b, X, F> {
 impl<'a, 'b, 'de, X, F> Visitor<'de> for Wrap<'a, 'b, X, F>
 where
     X: Visitor<'de>,
-    F: FnMut(Path),
+    F: Callback,
 {
     type Value = X::Value;
 
@@ -636,6 +1037,8 @@ where
             de: deserializer,
             callback: self.callback,
             path: Path::Some { parent: self.path },
+            context: self.context,
+            skipped: None,
         })
     }
 
@@ -647,6 +1050,8 @@ where
             de: deserializer,
             callback: self.callback,
             path: Path::NewtypeStruct { parent: self.path },
+            context: self.context,
+            skipped: None,
         })
     }
 
@@ -654,24 +1059,37 @@ where
     where
         V: de::SeqAccess<'de>,
     {
-        self.delegate
-            .visit_seq(SeqAccess::new(visitor, self.callback, self.path))
+        // A struct visited as a sequence takes one element per field.
+        let len = self.len.or(self.context.expected_fields().map(<[_]>::len));
+        self.delegate.visit_seq(SeqAccess::new(
+            visitor,
+            self.callback,
+            self.path,
+            self.context,
+            len,
+        ))
     }
 
     fn visit_map<V>(self, visitor: V) -> Result<Self::Value, V::Error>
     where
         V: de::MapAccess<'de>,
     {
-        self.delegate
-            .visit_map(MapAccess::new(visitor, self.callback, self.path))
+        self.delegate.visit_map(MapAccess::new(
+            visitor,
+            self.callback,
+            self.path,
+            self.context,
+        ))
     }
 
     fn visit_enum<V>(self, visitor: V) -> Result<Self::Value, V::Error>
     where
         V: de::EnumAccess<'de>,
     {
-        self.delegate
-            .visit_enum(Wrap::new(visitor, self.callback, self.path))
+        let wrap = Wrap::new(visitor, self.callback, self.path)
+            .with_context(self.context)
+            .with_variants(self.variants);
+        self.delegate.visit_enum(wrap)
     }
 
     fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
@@ -696,32 +1114,101 @@ where
     }
 }
 
-/// Forwarding impl to preserve context.
+/// Forwarding impl that also captures the variant identifier to track the
+/// path to the variant's contents.
 impl<'a, 'b, 'de, X, F> de::EnumAccess<'de> for Wrap<'a, 'b, X, F>
 where
     X: de::EnumAccess<'de> + 'a,
-    F: FnMut(Path) + 'b,
+    F: Callback + 'b,
 {
     type Error = X::Error;
-    type Variant = Wrap<'a, 'b, X::Variant, F>;
+    type Variant = VariantAccess<'a, 'b, X::Variant, F>;
 
     fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), X::Error>
     where
         V: DeserializeSeed<'de>,
     {
-        let callback = self.callback;
-        let path = self.path;
-        self.delegate
-            .variant_seed(seed)
-            .map(move |(v, vis)| (v, Wrap::new(vis, callback, path)))
+        let mut key = None;
+        let (value, variant) = self
+            .delegate
+            .variant_seed(CaptureKey::new(seed, &mut key))?;
+        let fallback = !self.variants.is_empty()
+            && key
+                .as_ref()
+                .is_some_and(|key| !is_expected(key, self.variants));
+        let name = variant_name(key, self.variants);
+        if fallback {
+            // The seed accepted a variant that the enum does not have, which
+            // serde_derive only does for a #[serde(other)] variant. What was
+            // discarded is the name, so that is what gets reported, at the
+            // path of the enum itself.
+            let mut context = self
+                .context
+                .with_reason(Reason::OtherVariant)
+                .with_variant(&name);
+            let value = Value::Str(name.clone());
+            if self.callback.captures_values() {
+                self.callback
+                    .ignored_value(self.path.clone(), &context, value)?;
+            } else {
+                if self.callback.inspects_values() {
+                    context = context.with_shape(Shape::of(&value));
+                }
+                self.callback.ignored(self.path.clone(), &context)?;
+            }
+        }
+        let path = Path::Variant {
+            parent: self.path,
+            name,
+        };
+        let variant = VariantAccess::new(variant, self.callback, path, self.context);
+        Ok((value, variant))
+    }
+}
+
+fn variant_name(key: Option<Key>, variants: &[&str]) -> String {
+    let index = match key {
+        Some(Key::Str(name)) => return name,
+        Some(Key::Bytes(name)) => match String::from_utf8(name) {
+            Ok(name) => return name,
+            Err(err) => return Key::Bytes(err.into_bytes()).to_string(),
+        },
+        Some(Key::Uint(index)) => usize::try_from(index).ok(),
+        Some(Key::Int(index)) => usize::try_from(index).ok(),
+        Some(key) => return key.to_string(),
+        None => None,
+    };
+    match index.and_then(|index| variants.get(index)) {
+        Some(name) => (*name).to_owned(),
+        None => String::from("?"),
+    }
+}
+
+/// Variant visitor that tracks the path through the variant it was created
+/// for.
+struct VariantAccess<'a, 'b, X, F: 'b> {
+    delegate: X,
+    callback: &'b mut F,
+    path: Path<'a>,
+    context: Context<'static>,
+}
+
+impl<'a, 'b, X, F> VariantAccess<'a, 'b, X, F> {
+    fn new(delegate: X, callback: &'b mut F, path: Path<'a>, context: Context<'static>) -> Self {
+        VariantAccess {
+            delegate,
+            callback,
+            path,
+            context,
+        }
     }
 }
 
 /// Forwarding impl to preserve context.
-impl<'a, 'b, 'de, X, F> de::VariantAccess<'de> for Wrap<'a, 'b, X, F>
+impl<'a, 'b, 'de, X, F> de::VariantAccess<'de> for VariantAccess<'a, 'b, X, F>
 where
     X: de::VariantAccess<'de>,
-    F: FnMut(Path),
+    F: Callback,
 {
     type Error = X::Error;
 
@@ -733,17 +1220,19 @@ where
     where
         T: DeserializeSeed<'de>,
     {
-        let path = Path::NewtypeVariant { parent: self.path };
-        self.delegate
-            .newtype_variant_seed(TrackedSeed::new(seed, self.callback, path))
+        let path = Path::NewtypeVariant { parent: &self.path };
+        let seed = TrackedSeed::new(seed, self.callback, path, self.context);
+        self.delegate.newtype_variant_seed(seed)
     }
 
     fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, X::Error>
     where
         V: Visitor<'de>,
     {
-        self.delegate
-            .tuple_variant(len, Wrap::new(visitor, self.callback, self.path))
+        let visitor = Wrap::new(visitor, self.callback, &self.path)
+            .with_context(self.context)
+            .with_len(len);
+        self.delegate.tuple_variant(len, visitor)
     }
 
     fn struct_variant<V>(
@@ -754,20 +1243,21 @@ where
     where
         V: Visitor<'de>,
     {
-        self.delegate
-            .struct_variant(fields, Wrap::new(visitor, self.callback, self.path))
+        let context = self.context.with_fields(fields);
+        let visitor = Wrap::new(visitor, self.callback, &self.path).with_context(context);
+        self.delegate.struct_variant(fields, visitor)
     }
 }
 
-/// Seed that saves the string into the given optional during `visit_str` and
-/// `visit_string`.
+/// Seed that saves the key into the given optional during `visit_str`,
+/// `visit_string` and the integer and bool visitor methods.
 struct CaptureKey<'a, X> {
     delegate: X,
-    key: &'a mut Option<String>,
+    key: &'a mut Option<Key>,
 }
 
 impl<'a, X> CaptureKey<'a, X> {
-    fn new(delegate: X, key: &'a mut Option<String>) -> Self {
+    fn new(delegate: X, key: &'a mut Option<Key>) -> Self {
         CaptureKey { delegate, key }
     }
 }
@@ -1054,8 +1544,9 @@ where
     where
         V: Visitor<'de>,
     {
-        self.delegate
-            .deserialize_ignored_any(CaptureKey::new(visitor, self.key))
+        // Whatever the input reports for data that is being skipped says
+        // nothing about the key, so leave it uncaptured.
+        self.delegate.deserialize_ignored_any(visitor)
     }
 
     fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, X::Error>
@@ -1071,7 +1562,8 @@ where
     }
 }
 
-/// Forwarding impl that also saves the value of integers and strings.
+/// Forwarding impl that also saves the value of the key, or a summary of it
+/// for sequences and maps.
 impl<'a, 'de, X> Visitor<'de> for CaptureKey<'a, X>
 where
     X: Visitor<'de>,
@@ -1086,7 +1578,7 @@ where
     where
         E: de::Error,
     {
-        *self.key = Some(v.to_string());
+        *self.key = Some(Key::Bool(v));
         self.delegate.visit_bool(v)
     }
 
@@ -1094,7 +1586,7 @@ where
     where
         E: de::Error,
     {
-        *self.key = Some(v.to_string());
+        *self.key = Some(Key::Int(i128::from(v)));
         self.delegate.visit_i8(v)
     }
 
@@ -1102,7 +1594,7 @@ where
     where
         E: de::Error,
     {
-        *self.key = Some(v.to_string());
+        *self.key = Some(Key::Int(i128::from(v)));
         self.delegate.visit_i16(v)
     }
 
@@ -1110,7 +1602,7 @@ where
     where
         E: de::Error,
     {
-        *self.key = Some(v.to_string());
+        *self.key = Some(Key::Int(i128::from(v)));
         self.delegate.visit_i32(v)
     }
 
@@ -1118,7 +1610,7 @@ where
     where
         E: de::Error,
     {
-        *self.key = Some(v.to_string());
+        *self.key = Some(Key::Int(i128::from(v)));
         self.delegate.visit_i64(v)
     }
 
@@ -1126,7 +1618,7 @@ where
     where
         E: de::Error,
     {
-        *self.key = Some(v.to_string());
+        *self.key = Some(Key::Int(v));
         self.delegate.visit_i128(v)
     }
 
@@ -1134,7 +1626,7 @@ where
     where
         E: de::Error,
     {
-        *self.key = Some(v.to_string());
+        *self.key = Some(Key::Uint(u128::from(v)));
         self.delegate.visit_u8(v)
     }
 
@@ -1142,7 +1634,7 @@ where
     where
         E: de::Error,
     {
-        *self.key = Some(v.to_string());
+        *self.key = Some(Key::Uint(u128::from(v)));
         self.delegate.visit_u16(v)
     }
 
@@ -1150,7 +1642,7 @@ where
     where
         E: de::Error,
     {
-        *self.key = Some(v.to_string());
+        *self.key = Some(Key::Uint(u128::from(v)));
         self.delegate.visit_u32(v)
     }
 
@@ -1158,7 +1650,7 @@ where
     where
         E: de::Error,
     {
-        *self.key = Some(v.to_string());
+        *self.key = Some(Key::Uint(u128::from(v)));
         self.delegate.visit_u64(v)
     }
 
@@ -1166,7 +1658,7 @@ where
     where
         E: de::Error,
     {
-        *self.key = Some(v.to_string());
+        *self.key = Some(Key::Uint(v));
         self.delegate.visit_u128(v)
     }
 
@@ -1174,6 +1666,7 @@ where
     where
         E: de::Error,
     {
+        *self.key = Some(Key::Float(f64::from(v)));
         self.delegate.visit_f32(v)
     }
 
@@ -1181,6 +1674,7 @@ where
     where
         E: de::Error,
     {
+        *self.key = Some(Key::Float(v));
         self.delegate.visit_f64(v)
     }
 
@@ -1188,6 +1682,7 @@ where
     where
         E: de::Error,
     {
+        *self.key = Some(Key::Char(v));
         self.delegate.visit_char(v)
     }
 
@@ -1195,7 +1690,7 @@ where
     where
         E: de::Error,
     {
-        *self.key = Some(v.to_owned());
+        *self.key = Some(Key::Str(v.to_owned()));
         self.delegate.visit_str(v)
     }
 
@@ -1203,7 +1698,7 @@ where
     where
         E: de::Error,
     {
-        *self.key = Some(v.to_owned());
+        *self.key = Some(Key::Str(v.to_owned()));
         self.delegate.visit_borrowed_str(v)
     }
 
@@ -1211,7 +1706,7 @@ where
     where
         E: de::Error,
     {
-        *self.key = Some(v.clone());
+        *self.key = Some(Key::Str(v.clone()));
         self.delegate.visit_string(v)
     }
 
@@ -1219,6 +1714,7 @@ where
     where
         E: de::Error,
     {
+        *self.key = Some(Key::Unit);
         self.delegate.visit_unit()
     }
 
@@ -1226,6 +1722,7 @@ where
     where
         E: de::Error,
     {
+        *self.key = Some(Key::Unit);
         self.delegate.visit_none()
     }
 
@@ -1233,7 +1730,8 @@ where
     where
         D: de::Deserializer<'de>,
     {
-        self.delegate.visit_some(deserializer)
+        self.delegate
+            .visit_some(CaptureKey::new(deserializer, self.key))
     }
 
     fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
@@ -1248,14 +1746,24 @@ where
     where
         V: de::SeqAccess<'de>,
     {
-        self.delegate.visit_seq(visitor)
+        let mut elements = Vec::new();
+        let value = self
+            .delegate
+            .visit_seq(CaptureSeq::new(visitor, &mut elements))?;
+        *self.key = Some(key::summarize_seq(&elements));
+        Ok(value)
     }
 
     fn visit_map<V>(self, visitor: V) -> Result<Self::Value, V::Error>
     where
         V: de::MapAccess<'de>,
     {
-        self.delegate.visit_map(visitor)
+        let mut entries = Vec::new();
+        let value = self
+            .delegate
+            .visit_map(CaptureMap::new(visitor, &mut entries))?;
+        *self.key = Some(key::summarize_map(&entries));
+        Ok(value)
     }
 
     fn visit_enum<V>(self, visitor: V) -> Result<Self::Value, V::Error>
@@ -1269,6 +1777,7 @@ where
     where
         E: de::Error,
     {
+        *self.key = Some(Key::Bytes(v.to_vec()));
         self.delegate.visit_bytes(v)
     }
 
@@ -1276,6 +1785,7 @@ where
     where
         E: de::Error,
     {
+        *self.key = Some(Key::Bytes(v.to_vec()));
         self.delegate.visit_borrowed_bytes(v)
     }
 
@@ -1283,6 +1793,7 @@ where
     where
         E: de::Error,
     {
+        *self.key = Some(Key::Bytes(v.clone()));
         self.delegate.visit_byte_buf(v)
     }
 }
@@ -1302,20 +1813,119 @@ where
     }
 }
 
-/// Seed used for map values, sequence elements and newtype variants to track
-/// their path.
+/// Seq visitor that captures each element of a sequence used as a map key.
+struct CaptureSeq<'a, X> {
+    delegate: X,
+    elements: &'a mut Vec<Option<Key>>,
+}
+
+impl<'a, X> CaptureSeq<'a, X> {
+    fn new(delegate: X, elements: &'a mut Vec<Option<Key>>) -> Self {
+        CaptureSeq { delegate, elements }
+    }
+}
+
+impl<'a, 'de, X> de::SeqAccess<'de> for CaptureSeq<'a, X>
+where
+    X: de::SeqAccess<'de>,
+{
+    type Error = X::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, X::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let mut element = None;
+        let value = self
+            .delegate
+            .next_element_seed(CaptureKey::new(seed, &mut element))?;
+        if value.is_some() {
+            self.elements.push(element);
+        }
+        Ok(value)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.delegate.size_hint()
+    }
+}
+
+/// Map visitor that captures each entry of a map used as a map key.
+struct CaptureMap<'a, X> {
+    delegate: X,
+    entries: &'a mut Vec<(Option<Key>, Option<Key>)>,
+}
+
+impl<'a, X> CaptureMap<'a, X> {
+    fn new(delegate: X, entries: &'a mut Vec<(Option<Key>, Option<Key>)>) -> Self {
+        CaptureMap { delegate, entries }
+    }
+}
+
+impl<'a, 'de, X> de::MapAccess<'de> for CaptureMap<'a, X>
+where
+    X: de::MapAccess<'de>,
+{
+    type Error = X::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, X::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        let mut key = None;
+        let value = self
+            .delegate
+            .next_key_seed(CaptureKey::new(seed, &mut key))?;
+        if value.is_some() {
+            self.entries.push((key, None));
+        }
+        Ok(value)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, X::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let mut value = None;
+        let result = self
+            .delegate
+            .next_value_seed(CaptureKey::new(seed, &mut value))?;
+        if let Some(entry) = self.entries.last_mut() {
+            entry.1 = value;
+        }
+        Ok(result)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.delegate.size_hint()
+    }
+}
+
+/// Seed used for map keys, map values, sequence elements and newtype variants
+/// to track their path.
 struct TrackedSeed<'a, X, F: 'a> {
     seed: X,
     callback: &'a mut F,
     path: Path<'a>,
+    context: Context<'static>,
+    skipped: Option<&'a Cell<bool>>,
 }
 
 impl<'a, X, F> TrackedSeed<'a, X, F> {
-    fn new(seed: X, callback: &'a mut F, path: Path<'a>) -> Self {
+    fn new(seed: X, callback: &'a mut F, path: Path<'a>, context: Context<'static>) -> Self {
         TrackedSeed {
             seed,
             callback,
             path,
+            context,
+            skipped: None,
+        }
+    }
+
+    fn with_skipped(self, skipped: &'a Cell<bool>) -> Self {
+        TrackedSeed {
+            skipped: Some(skipped),
+            ..self
         }
     }
 }
@@ -1323,7 +1933,7 @@ impl<'a, X, F> TrackedSeed<'a, X, F> {
 impl<'a, 'de, X, F> DeserializeSeed<'de> for TrackedSeed<'a, X, F>
 where
     X: DeserializeSeed<'de>,
-    F: FnMut(Path),
+    F: Callback,
 {
     type Value = X::Value;
 
@@ -1335,6 +1945,8 @@ where
             de: deserializer,
             callback: self.callback,
             path: self.path,
+            context: self.context,
+            skipped: self.skipped,
         })
     }
 }
@@ -1344,16 +1956,33 @@ struct SeqAccess<'a, 'b, X, F: 'b> {
     delegate: X,
     callback: &'b mut F,
     path: &'a Path<'a>,
+    context: Context<'static>,
     index: usize,
+    // Declared length of a tuple, or number of fields of a struct.
+    len: Option<usize>,
+    // Whether the visitor has deserialized any element rather than skipping
+    // it, and whether it skipped the latest one.
+    used: bool,
+    skipped: Cell<bool>,
 }
 
 impl<'a, 'b, X, F> SeqAccess<'a, 'b, X, F> {
-    fn new(delegate: X, callback: &'b mut F, path: &'a Path<'a>) -> Self {
+    fn new(
+        delegate: X,
+        callback: &'b mut F,
+        path: &'a Path<'a>,
+        context: Context<'static>,
+        len: Option<usize>,
+    ) -> Self {
         SeqAccess {
             delegate,
             callback,
             path,
+            context,
             index: 0,
+            len,
+            used: false,
+            skipped: Cell::new(false),
         }
     }
 }
@@ -1362,7 +1991,7 @@ impl<'a, 'b, X, F> SeqAccess<'a, 'b, X, F> {
 impl<'a, 'b, 'de, X, F> de::SeqAccess<'de> for SeqAccess<'a, 'b, X, F>
 where
     X: de::SeqAccess<'de>,
-    F: FnMut(Path),
+    F: Callback,
 {
     type Error = X::Error;
 
@@ -1374,9 +2003,26 @@ where
             parent: self.path,
             index: self.index,
         };
+        // Elements past the end of a tuple, or skipped after the visitor
+        // already took what it needed, are trailing data. Skipping from the
+        // start, as Vec<IgnoredAny> does, is not.
+        let trailing = match self.len {
+            Some(len) => self.index >= len,
+            None => self.used,
+        };
         self.index += 1;
-        self.delegate
-            .next_element_seed(TrackedSeed::new(seed, self.callback, path))
+        let context = if trailing {
+            self.context.with_reason(Reason::TrailingData)
+        } else {
+            self.context
+        };
+        self.skipped.set(false);
+        let seed = TrackedSeed::new(seed, self.callback, path, context).with_skipped(&self.skipped);
+        let element = self.delegate.next_element_seed(seed)?;
+        if element.is_some() && !self.skipped.get() {
+            self.used = true;
+        }
+        Ok(element)
     }
 
     fn size_hint(&self) -> Option<usize> {
@@ -1384,37 +2030,85 @@ where
     }
 }
 
-/// Map visitor that captures the string value of its keys and uses that to
+/// Map visitor that captures the value of its keys and uses that to
 /// track the path to its values.
 struct MapAccess<'a, 'b, X, F: 'b> {
     delegate: X,
     callback: &'b mut F,
     path: &'a Path<'a>,
-    key: Option<String>,
+    context: Context<'static>,
+    key: Option<Key>,
+    // The fields of the struct, and which of them have been seen, if the
+    // callback tracks missing fields and this map is a struct.
+    fields: Option<(&'static [&'static str], Vec<bool>)>,
+    // Keys seen so far, if the callback tracks duplicate keys.
+    keys: Option<BTreeSet<Key>>,
 }
 
-impl<'a, 'b, X, F> MapAccess<'a, 'b, X, F> {
-    fn new(delegate: X, callback: &'b mut F, path: &'a Path<'a>) -> Self {
+impl<'a, 'b, X, F> MapAccess<'a, 'b, X, F>
+where
+    F: Callback,
+{
+    fn new(
+        delegate: X,
+        callback: &'b mut F,
+        path: &'a Path<'a>,
+        context: Context<'static>,
+    ) -> Self {
+        let fields = match context.expected_fields() {
+            Some(fields) if callback.tracks_missing() => Some((fields, vec![false; fields.len()])),
+            _ => None,
+        };
+        let keys = if callback.tracks_duplicates() {
+            Some(BTreeSet::new())
+        } else {
+            None
+        };
         MapAccess {
             delegate,
             callback,
             path,
+            context,
             key: None,
+            fields,
+            keys,
         }
     }
 
-    fn key<E>(&mut self) -> Result<String, E>
+    fn report_missing<E>(&mut self) -> Result<(), E>
     where
         E: de::Error,
     {
-        self.key.take().ok_or_else(|| E::custom("non-string key"))
+        let Some((fields, seen)) = self.fields.take() else {
+            return Ok(());
+        };
+        for (field, seen) in fields.iter().zip(seen) {
+            if !seen {
+                let path = Path::Map {
+                    parent: self.path,
+                    key: Key::from(*field),
+                };
+                self.callback.missing(path, &self.context)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Every visitor method of CaptureKey saves a key, but a Deserialize impl
+    // is free to consume its key without calling any of them. Reporting such
+    // an entry with a placeholder is better than failing a parse that would
+    // have succeeded without this crate.
+    fn key(&mut self) -> Key {
+        self.key
+            .take()
+            .unwrap_or_else(|| Key::Other(String::from("?")))
     }
 }
 
 impl<'a, 'b, 'de, X, F> de::MapAccess<'de> for MapAccess<'a, 'b, X, F>
 where
     X: de::MapAccess<'de>,
-    F: FnMut(Path),
+    F: Callback,
 {
     type Error = X::Error;
 
@@ -1422,8 +2116,30 @@ where
     where
         K: DeserializeSeed<'de>,
     {
-        self.delegate
-            .next_key_seed(CaptureKey::new(seed, &mut self.key))
+        let path = Path::MapKey { parent: self.path };
+        let seed = TrackedSeed::new(seed, self.callback, path, self.context);
+        let key = self
+            .delegate
+            .next_key_seed(CaptureKey::new(seed, &mut self.key))?;
+        if key.is_none() {
+            self.report_missing()?;
+            return Ok(None);
+        }
+        if let (Some((fields, seen)), Some(key)) = (&mut self.fields, &self.key) {
+            if let Some(index) = position(key, fields) {
+                seen[index] = true;
+            }
+        }
+        if let (Some(keys), Some(key)) = (&mut self.keys, &self.key) {
+            if !keys.insert(key.clone()) {
+                let path = Path::Map {
+                    parent: self.path,
+                    key: key.clone(),
+                };
+                self.callback.duplicate(path, &self.context)?;
+            }
+        }
+        Ok(key)
     }
 
     fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, X::Error>
@@ -1432,10 +2148,10 @@ where
     {
         let path = Path::Map {
             parent: self.path,
-            key: self.key()?,
+            key: self.key(),
         };
-        self.delegate
-            .next_value_seed(TrackedSeed::new(seed, self.callback, path))
+        let seed = TrackedSeed::new(seed, self.callback, path, self.context);
+        self.delegate.next_value_seed(seed)
     }
 
     fn size_hint(&self) -> Option<usize> {
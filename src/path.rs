@@ -0,0 +1,428 @@
+use crate::key::Key;
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Display};
+use core::iter::FusedIterator;
+
+/// Path to the current value in the input, like `dependencies.serde.typo1`.
+#[derive(Clone)]
+pub enum Path<'a> {
+    Root,
+    Seq {
+        parent: &'a Path<'a>,
+        index: usize,
+    },
+    Map {
+        parent: &'a Path<'a>,
+        key: Key,
+    },
+    /// Inside the key of a map entry, rather than its value. Only reachable
+    /// for formats whose map keys can be structs, sequences or maps, such as
+    /// RON, YAML and CBOR.
+    MapKey {
+        parent: &'a Path<'a>,
+    },
+    /// Inside the named variant of an enum.
+    Variant {
+        parent: &'a Path<'a>,
+        name: String,
+    },
+    Some {
+        parent: &'a Path<'a>,
+    },
+    NewtypeStruct {
+        parent: &'a Path<'a>,
+    },
+    NewtypeVariant {
+        parent: &'a Path<'a>,
+    },
+}
+
+impl<'a> Path<'a> {
+    /// The path one level up, or `None` for the root.
+    #[must_use]
+    pub fn parent(&self) -> Option<&'a Path<'a>> {
+        match *self {
+            Path::Root => None,
+            Path::Seq { parent, .. }
+            | Path::Map { parent, .. }
+            | Path::MapKey { parent }
+            | Path::Variant { parent, .. }
+            | Path::Some { parent }
+            | Path::NewtypeStruct { parent }
+            | Path::NewtypeVariant { parent } => Some(parent),
+        }
+    }
+
+    /// The final segment of this path, or `None` for the root.
+    #[must_use]
+    pub fn segment(&self) -> Option<Segment<'_>> {
+        match *self {
+            Path::Root => None,
+            Path::Seq { index, .. } => Some(Segment::Seq { index }),
+            Path::Map { ref key, .. } => Some(Segment::Map { key }),
+            Path::MapKey { .. } => Some(Segment::MapKey),
+            Path::Variant { ref name, .. } => Some(Segment::Variant { name }),
+            Path::Some { .. } => Some(Segment::Some),
+            Path::NewtypeStruct { .. } => Some(Segment::NewtypeStruct),
+            Path::NewtypeVariant { .. } => Some(Segment::NewtypeVariant),
+        }
+    }
+
+    /// Number of segments between the root and this path. The root has depth
+    /// 0.
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        let mut depth = 0;
+        let mut path = self;
+        while let Some(parent) = path.parent() {
+            depth += 1;
+            path = parent;
+        }
+        depth
+    }
+
+    /// Iterates over the segments of this path from the root to the leaf,
+    /// without allocating.
+    #[must_use]
+    pub fn segments(&self) -> Segments<'_> {
+        let depth = self.depth();
+        Segments {
+            path: self,
+            depth,
+            front: 0,
+            back: depth,
+        }
+    }
+
+    /// Iterates over this path and each of its parents, ending with the root.
+    #[must_use]
+    pub fn ancestors(&self) -> Ancestors<'_> {
+        Ancestors { next: Some(self) }
+    }
+
+    /// Copies this path into an owned `PathBuf` that can outlive the
+    /// deserialization, for example to be stored in a set.
+    pub fn to_owned(&self) -> PathBuf {
+        PathBuf {
+            segments: self.segments().map(Segment::to_owned).collect(),
+        }
+    }
+}
+
+impl<'a> Display for Path<'a> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        struct Parent<'a>(&'a Path<'a>);
+
+        impl<'a> Display for Parent<'a> {
+            fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+                match *self.0 {
+                    Path::Root => Ok(()),
+                    ref path => write!(formatter, "{}.", path),
+                }
+            }
+        }
+
+        match *self {
+            Path::Root => formatter.write_str("."),
+            Path::Seq { parent, index } => write!(formatter, "{}{}", Parent(parent), index),
+            Path::Map { parent, ref key } => write!(formatter, "{}{}", Parent(parent), key),
+            Path::MapKey { parent } => write!(formatter, "{}?key", Parent(parent)),
+            Path::Variant { parent, ref name } => write!(formatter, "{}{}", Parent(parent), name),
+            Path::Some { parent }
+            | Path::NewtypeStruct { parent }
+            | Path::NewtypeVariant { parent } => write!(formatter, "{}?", Parent(parent)),
+        }
+    }
+}
+
+/// One step of a [`Path`], borrowed from it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Segment<'a> {
+    Seq { index: usize },
+    Map { key: &'a Key },
+    MapKey,
+    Variant { name: &'a str },
+    Some,
+    NewtypeStruct,
+    NewtypeVariant,
+}
+
+impl<'a> Segment<'a> {
+    /// Copies this segment into an owned `SegmentBuf`.
+    #[must_use]
+    pub fn to_owned(self) -> SegmentBuf {
+        match self {
+            Segment::Seq { index } => SegmentBuf::Seq { index },
+            Segment::Map { key } => SegmentBuf::Map { key: key.clone() },
+            Segment::MapKey => SegmentBuf::MapKey,
+            Segment::Variant { name } => SegmentBuf::Variant {
+                name: name.to_owned(),
+            },
+            Segment::Some => SegmentBuf::Some,
+            Segment::NewtypeStruct => SegmentBuf::NewtypeStruct,
+            Segment::NewtypeVariant => SegmentBuf::NewtypeVariant,
+        }
+    }
+}
+
+impl<'a> Display for Segment<'a> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            Segment::Seq { index } => Display::fmt(&index, formatter),
+            Segment::Map { key } => Display::fmt(key, formatter),
+            Segment::MapKey => formatter.write_str("?key"),
+            Segment::Variant { name } => formatter.write_str(name),
+            Segment::Some | Segment::NewtypeStruct | Segment::NewtypeVariant => {
+                formatter.write_str("?")
+            }
+        }
+    }
+}
+
+/// Iterator over the segments of a [`Path`], from the root to the leaf.
+///
+/// Returned by [`Path::segments`].
+#[derive(Clone)]
+pub struct Segments<'a> {
+    path: &'a Path<'a>,
+    depth: usize,
+    // Range of segments not yet yielded. Segment `i` is the final segment of
+    // the ancestor at depth `i + 1`.
+    front: usize,
+    back: usize,
+}
+
+impl<'a> Segments<'a> {
+    fn nth_segment(&self, i: usize) -> Segment<'a> {
+        let mut path = self.path;
+        for _ in i + 1..self.depth {
+            path = path.parent().unwrap();
+        }
+        path.segment().unwrap()
+    }
+}
+
+impl<'a> Iterator for Segments<'a> {
+    type Item = Segment<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        let segment = self.nth_segment(self.front);
+        self.front += 1;
+        Some(segment)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.front = self.front.saturating_add(n).min(self.back);
+        self.next()
+    }
+}
+
+impl<'a> DoubleEndedIterator for Segments<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(self.nth_segment(self.back))
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.back = self.back.saturating_sub(n).max(self.front);
+        self.next_back()
+    }
+}
+
+impl<'a> ExactSizeIterator for Segments<'a> {}
+
+impl<'a> FusedIterator for Segments<'a> {}
+
+/// Iterator over a [`Path`] and its parents, from the leaf to the root.
+///
+/// Returned by [`Path::ancestors`].
+pub struct Ancestors<'a> {
+    next: Option<&'a Path<'a>>,
+}
+
+impl<'a> Iterator for Ancestors<'a> {
+    type Item = &'a Path<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let path = self.next?;
+        self.next = path.parent();
+        Some(path)
+    }
+}
+
+impl<'a> FusedIterator for Ancestors<'a> {}
+
+/// Owned counterpart of [`Path`], stored as a list of segments from the root.
+///
+/// Unlike `Path`, which borrows its parents from the deserializer's stack, a
+/// `PathBuf` can be kept after the callback returns, compared, hashed and
+/// placed in ordered or hashed collections.
+#[derive(Clone, Default, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PathBuf {
+    segments: Vec<SegmentBuf>,
+}
+
+/// One step of a [`PathBuf`].
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SegmentBuf {
+    Seq { index: usize },
+    Map { key: Key },
+    MapKey,
+    Variant { name: String },
+    Some,
+    NewtypeStruct,
+    NewtypeVariant,
+}
+
+impl PathBuf {
+    /// The root path, containing no segments.
+    #[must_use]
+    pub fn new() -> Self {
+        PathBuf {
+            segments: Vec::new(),
+        }
+    }
+
+    /// Segments of the path, ordered from the root to the leaf.
+    #[must_use]
+    pub fn segments(&self) -> &[SegmentBuf] {
+        &self.segments
+    }
+
+    /// Whether this is the root path.
+    #[must_use]
+    pub fn is_root(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// The path without its final segment, or `None` for the root.
+    #[must_use]
+    pub fn parent(&self) -> Option<PathBuf> {
+        let (_last, rest) = self.segments.split_last()?;
+        Some(PathBuf {
+            segments: rest.to_vec(),
+        })
+    }
+
+    /// Whether `base` is a prefix of this path. Every path starts with the
+    /// root and with itself.
+    #[must_use]
+    pub fn starts_with(&self, base: &PathBuf) -> bool {
+        self.segments.starts_with(&base.segments)
+    }
+
+    /// Appends a segment to the end of the path.
+    pub fn push(&mut self, segment: SegmentBuf) {
+        self.segments.push(segment);
+    }
+
+    /// Iterates over the segments as borrowed `Segment`s, from the root to the
+    /// leaf.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = Segment<'_>> + ExactSizeIterator {
+        self.segments.iter().map(SegmentBuf::as_segment)
+    }
+
+    /// Removes and returns the final segment, or `None` for the root.
+    pub fn pop(&mut self) -> Option<SegmentBuf> {
+        self.segments.pop()
+    }
+}
+
+impl<'a> From<&Path<'a>> for PathBuf {
+    fn from(path: &Path<'a>) -> Self {
+        path.to_owned()
+    }
+}
+
+impl FromIterator<SegmentBuf> for PathBuf {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = SegmentBuf>,
+    {
+        PathBuf {
+            segments: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Extend<SegmentBuf> for PathBuf {
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = SegmentBuf>,
+    {
+        self.segments.extend(iter);
+    }
+}
+
+/// Renders the same way as the `Display` impl of [`Path`].
+impl Display for PathBuf {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let mut segments = self.segments.iter();
+        match segments.next() {
+            None => return formatter.write_str("."),
+            Some(first) => Display::fmt(first, formatter)?,
+        }
+        for segment in segments {
+            formatter.write_str(".")?;
+            Display::fmt(segment, formatter)?;
+        }
+        Ok(())
+    }
+}
+
+impl SegmentBuf {
+    // The path made of `parent` followed by this segment.
+    pub(crate) fn under<'a>(&self, parent: &'a Path<'a>) -> Path<'a> {
+        match self {
+            SegmentBuf::Seq { index } => Path::Seq {
+                parent,
+                index: *index,
+            },
+            SegmentBuf::Map { key } => Path::Map {
+                parent,
+                key: key.clone(),
+            },
+            SegmentBuf::MapKey => Path::MapKey { parent },
+            SegmentBuf::Variant { name } => Path::Variant {
+                parent,
+                name: name.clone(),
+            },
+            SegmentBuf::Some => Path::Some { parent },
+            SegmentBuf::NewtypeStruct => Path::NewtypeStruct { parent },
+            SegmentBuf::NewtypeVariant => Path::NewtypeVariant { parent },
+        }
+    }
+
+    /// Borrows this segment as a `Segment`.
+    #[must_use]
+    pub fn as_segment(&self) -> Segment<'_> {
+        match self {
+            SegmentBuf::Seq { index } => Segment::Seq { index: *index },
+            SegmentBuf::Map { key } => Segment::Map { key },
+            SegmentBuf::MapKey => Segment::MapKey,
+            SegmentBuf::Variant { name } => Segment::Variant { name },
+            SegmentBuf::Some => Segment::Some,
+            SegmentBuf::NewtypeStruct => Segment::NewtypeStruct,
+            SegmentBuf::NewtypeVariant => Segment::NewtypeVariant,
+        }
+    }
+}
+
+impl Display for SegmentBuf {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        Display::fmt(&self.as_segment(), formatter)
+    }
+}
@@ -0,0 +1,370 @@
+use crate::error::ParseError;
+use crate::key::Key;
+use crate::path::{Path, PathBuf, Segment, SegmentBuf};
+use alloc::borrow::ToOwned;
+use alloc::string::{String, ToString as _};
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display, Write as _};
+use core::iter::Peekable;
+use core::str::{CharIndices, FromStr};
+
+/// Glob-style pattern over paths, like `servers.*.x-*`.
+///
+/// A pattern is a list of segments separated by `.`, each matched against one
+/// segment of the path as it is displayed:
+///
+/// - `*` matches any run of characters within a segment, so `x-*` matches
+///   every key that starts with `x-`.
+/// - `?` matches any single character.
+/// - `[0-9]`, `[abc]` and `[!a-z]` match one character that is, or with `!`
+///   is not, in the set.
+/// - `**` as a whole segment matches any number of segments, including none,
+///   so `**.comment` matches a `comment` key at any depth.
+/// - `\` makes the next character literal, as in `hosts.example\.com` for a
+///   key that contains a dot.
+///
+/// Option and newtype segments do not correspond to any nesting in the input,
+/// so they are skipped when matching: `list.*.name` matches
+/// `list.0.?.name`.
+///
+/// ```
+/// # use serde_derive::Deserialize;
+/// # use std::collections::BTreeMap as Map;
+/// use serde_ignored::PathPattern;
+///
+/// #[derive(Deserialize)]
+/// struct Config {
+///     servers: Map<String, Server>,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Server {
+///     port: u16,
+/// }
+///
+/// let extensions = PathPattern::new("servers.*.x-*")?;
+///
+/// let j = r#"{"servers": {"a": {"port": 80, "x-owner": "ops", "prot": 1}}}"#;
+/// let jd = &mut serde_json::Deserializer::from_str(j);
+/// let mut unused = Vec::new();
+///
+/// let _: Config = serde_ignored::deserialize(jd, |path| {
+///     if !path.matches(&extensions) {
+///         unused.push(path.to_string());
+///     }
+/// })?;
+///
+/// assert_eq!(unused, ["servers.a.prot"]);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct PathPattern {
+    source: String,
+    parts: Vec<Part>,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum Part {
+    AnyDepth,
+    Glob(Vec<Token>),
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum Token {
+    Char(char),
+    AnyChar,
+    AnyString,
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+impl PathPattern {
+    /// Compiles a pattern, failing if it contains an empty segment, an
+    /// unclosed `[`, or a trailing `\`.
+    pub fn new(pattern: &str) -> Result<PathPattern, ParseError> {
+        let mut parser = Parser {
+            input: pattern,
+            chars: pattern.char_indices().peekable(),
+        };
+        Ok(PathPattern {
+            source: pattern.to_owned(),
+            parts: parser.parts()?,
+        })
+    }
+
+    /// The pattern as it was written.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    fn matches<'a, I>(&self, segments: I) -> bool
+    where
+        I: DoubleEndedIterator<Item = Segment<'a>> + Clone,
+    {
+        matches(&self.parts, segments)
+    }
+}
+
+impl FromStr for PathPattern {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PathPattern::new(s)
+    }
+}
+
+impl Display for PathPattern {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(&self.source)
+    }
+}
+
+impl Debug for PathPattern {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_tuple("PathPattern")
+            .field(&self.source)
+            .finish()
+    }
+}
+
+impl<'a> Path<'a> {
+    /// Whether this path matches the pattern. See [`PathPattern`] for the
+    /// syntax.
+    #[must_use]
+    pub fn matches(&self, pattern: &PathPattern) -> bool {
+        // Segments is a pair of indices into the path, so the copies that
+        // `**` backtracks to are cheap and nothing is allocated.
+        pattern.matches(self.segments())
+    }
+}
+
+impl PathBuf {
+    /// Whether this path matches the pattern. See [`Path::matches`].
+    pub fn matches(&self, pattern: &PathPattern) -> bool {
+        pattern.matches(self.segments().iter().map(SegmentBuf::as_segment))
+    }
+}
+
+fn matches<'a, I>(parts: &[Part], mut segments: I) -> bool
+where
+    I: DoubleEndedIterator<Item = Segment<'a>> + Clone,
+{
+    // Segments after the last `**` are matched from the back, so that
+    // `**.comment` looks at the last segment before trying every depth.
+    if let Some((Part::Glob(tokens), rest)) = parts.split_last() {
+        return match next_back_nested(&mut segments) {
+            Some(segment) => {
+                with_text(segment, |text| glob(tokens, text)) && matches(rest, segments)
+            }
+            None => false,
+        };
+    }
+    match parts.split_first() {
+        None => next_nested(&mut segments).is_none(),
+        Some((Part::AnyDepth, rest)) => loop {
+            if matches(rest, segments.clone()) {
+                return true;
+            }
+            if next_nested(&mut segments).is_none() {
+                return false;
+            }
+        },
+        Some((Part::Glob(tokens), rest)) => match next_nested(&mut segments) {
+            Some(segment) => {
+                with_text(segment, |text| glob(tokens, text)) && matches(rest, segments)
+            }
+            None => false,
+        },
+    }
+}
+
+// Next segment that corresponds to a level of nesting in the input.
+fn next_nested<'a, I>(segments: &mut I) -> Option<Segment<'a>>
+where
+    I: Iterator<Item = Segment<'a>>,
+{
+    segments.find(|segment| {
+        !matches!(
+            segment,
+            Segment::Some | Segment::NewtypeStruct | Segment::NewtypeVariant
+        )
+    })
+}
+
+fn next_back_nested<'a, I>(segments: &mut I) -> Option<Segment<'a>>
+where
+    I: DoubleEndedIterator<Item = Segment<'a>>,
+{
+    segments.rfind(|segment| {
+        !matches!(
+            segment,
+            Segment::Some | Segment::NewtypeStruct | Segment::NewtypeVariant
+        )
+    })
+}
+
+// Calls `f` with the segment as displayed, formatting it on the stack unless
+// it is too long.
+fn with_text<R>(segment: Segment, f: impl FnOnce(&str) -> R) -> R {
+    match segment {
+        Segment::Map {
+            key: Key::Str(s) | Key::Other(s),
+        } => f(s),
+        Segment::Variant { name } => f(name),
+        segment => {
+            let mut buffer = Buffer {
+                bytes: [0; 64],
+                len: 0,
+            };
+            if write!(buffer, "{}", segment).is_ok() {
+                f(buffer.as_str())
+            } else {
+                f(&segment.to_string())
+            }
+        }
+    }
+}
+
+struct Buffer {
+    bytes: [u8; 64],
+    len: usize,
+}
+
+impl Buffer {
+    fn as_str(&self) -> &str {
+        // Only ever filled with whole `str`s.
+        core::str::from_utf8(&self.bytes[..self.len]).unwrap()
+    }
+}
+
+impl fmt::Write for Buffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        let dst = self.bytes.get_mut(self.len..end).ok_or(fmt::Error)?;
+        dst.copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+fn glob(tokens: &[Token], text: &str) -> bool {
+    match tokens.split_first() {
+        None => text.is_empty(),
+        Some((Token::AnyString, rest)) => text
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain([text.len()])
+            .any(|i| glob(rest, &text[i..])),
+        Some((token, rest)) => {
+            let mut chars = text.chars();
+            match chars.next() {
+                Some(ch) if token.matches(ch) => glob(rest, chars.as_str()),
+                _ => false,
+            }
+        }
+    }
+}
+
+impl Token {
+    fn matches(&self, ch: char) -> bool {
+        match self {
+            Token::Char(expected) => ch == *expected,
+            Token::AnyChar | Token::AnyString => true,
+            Token::Class { negated, ranges } => {
+                ranges.iter().any(|&(lo, hi)| lo <= ch && ch <= hi) != *negated
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn parts(&mut self) -> Result<Vec<Part>, ParseError> {
+        let mut parts = Vec::new();
+        let mut start = 0;
+        let mut tokens = Vec::new();
+        loop {
+            let (end, ch) = match self.chars.next() {
+                Some((i, ch)) => (i, Some(ch)),
+                None => (self.input.len(), None),
+            };
+            match ch {
+                None | Some('.') => {
+                    if tokens.is_empty() {
+                        return Err(ParseError::new("empty segment", start));
+                    }
+                    if &self.input[start..end] == "**" {
+                        parts.push(Part::AnyDepth);
+                        tokens.clear();
+                    } else {
+                        parts.push(Part::Glob(core::mem::take(&mut tokens)));
+                    }
+                    if ch.is_none() {
+                        return Ok(parts);
+                    }
+                    start = end + 1;
+                }
+                Some('*') => {
+                    if tokens.last() != Some(&Token::AnyString) {
+                        tokens.push(Token::AnyString);
+                    }
+                }
+                Some('?') => tokens.push(Token::AnyChar),
+                Some('[') => tokens.push(self.class(end)?),
+                Some('\\') => tokens.push(Token::Char(self.escaped(end)?)),
+                Some(ch) => tokens.push(Token::Char(ch)),
+            }
+        }
+    }
+
+    // Parses the rest of a character class whose `[` is at `start`.
+    fn class(&mut self, start: usize) -> Result<Token, ParseError> {
+        let negated = self.chars.next_if(|&(_, ch)| ch == '!').is_some();
+        let mut ranges = Vec::new();
+        loop {
+            let lo = match self.chars.next() {
+                None => return Err(ParseError::new("unclosed '['", start)),
+                Some((i, ']')) if ranges.is_empty() => {
+                    return Err(ParseError::new("empty character class", i));
+                }
+                Some((_, ']')) => return Ok(Token::Class { negated, ranges }),
+                Some((i, '\\')) => self.escaped(i)?,
+                Some((_, ch)) => ch,
+            };
+            let mut hi = lo;
+            if let Some((i, _)) = self.chars.next_if(|&(_, ch)| ch == '-') {
+                hi = match self.chars.next() {
+                    None => return Err(ParseError::new("unclosed '['", start)),
+                    Some((_, ']')) => {
+                        // A `-` before the closing bracket is literal.
+                        ranges.push((lo, lo));
+                        ranges.push(('-', '-'));
+                        return Ok(Token::Class { negated, ranges });
+                    }
+                    Some((j, '\\')) => self.escaped(j)?,
+                    Some((_, ch)) => ch,
+                };
+                if hi < lo {
+                    return Err(ParseError::new("character range out of order", i));
+                }
+            }
+            ranges.push((lo, hi));
+        }
+    }
+
+    // Parses the character after a `\` at `start`.
+    fn escaped(&mut self, start: usize) -> Result<char, ParseError> {
+        match self.chars.next() {
+            Some((_, ch)) => Ok(ch),
+            None => Err(ParseError::new("expected a character after '\\'", start)),
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! [RFC 6901] JSON Pointer representation of paths.
+//!
+//! [RFC 6901]: https://www.rfc-editor.org/rfc/rfc6901
+
+use crate::error::ParseError;
+use crate::key::Key;
+use crate::path::{Path, PathBuf, Segment, SegmentBuf};
+use alloc::string::String;
+use core::fmt::{self, Write as _};
+
+impl<'a> Path<'a> {
+    /// Renders this path as a JSON Pointer, like `/dependencies/serde/typo1`.
+    ///
+    /// `~` and `/` inside keys are escaped as `~0` and `~1`. Option and newtype
+    /// segments do not correspond to any nesting in the document, so they are
+    /// left out. JSON object keys are always strings, so a `MapKey` segment
+    /// has no equivalent either and is also left out. The root renders as the
+    /// empty string.
+    #[must_use]
+    pub fn to_json_pointer(&self) -> String {
+        to_json_pointer(self.segments())
+    }
+}
+
+impl PathBuf {
+    /// Renders this path as a JSON Pointer. See [`Path::to_json_pointer`].
+    #[must_use]
+    pub fn to_json_pointer(&self) -> String {
+        to_json_pointer(self.iter())
+    }
+
+    /// Parses a JSON Pointer such as `/dependencies/serde/typo1`.
+    ///
+    /// A JSON Pointer does not say whether a reference token names an array
+    /// element or an object member, so tokens that are valid array indices
+    /// (`0`, or digits without a leading zero) become `Seq` segments and
+    /// all other tokens become `Map` segments.
+    pub fn from_json_pointer(pointer: &str) -> Result<PathBuf, ParseError> {
+        let mut path = PathBuf::new();
+        if pointer.is_empty() {
+            return Ok(path);
+        }
+        if !pointer.starts_with('/') {
+            return Err(ParseError::new("JSON Pointer must start with '/'", 0));
+        }
+
+        let mut offset = 1;
+        for token in pointer[1..].split('/') {
+            path.push(parse_token(token, offset)?);
+            offset += token.len() + 1;
+        }
+        Ok(path)
+    }
+}
+
+fn to_json_pointer<'a, I>(segments: I) -> String
+where
+    I: Iterator<Item = Segment<'a>>,
+{
+    let mut pointer = String::new();
+    for segment in segments {
+        match segment {
+            Segment::Seq { index } => {
+                let _ = write!(pointer, "/{}", index);
+            }
+            Segment::Map { key } => {
+                pointer.push('/');
+                let _ = write!(Escape(&mut pointer), "{}", key);
+            }
+            Segment::Variant { name } => {
+                pointer.push('/');
+                let _ = Escape(&mut pointer).write_str(name);
+            }
+            Segment::MapKey | Segment::Some | Segment::NewtypeStruct | Segment::NewtypeVariant => {}
+        }
+    }
+    pointer
+}
+
+fn parse_token(token: &str, offset: usize) -> Result<SegmentBuf, ParseError> {
+    if is_array_index(token) {
+        if let Ok(index) = token.parse() {
+            return Ok(SegmentBuf::Seq { index });
+        }
+    }
+
+    let mut key = String::with_capacity(token.len());
+    let mut chars = token.char_indices();
+    while let Some((i, ch)) = chars.next() {
+        if ch != '~' {
+            key.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some((_, '0')) => key.push('~'),
+            Some((_, '1')) => key.push('/'),
+            _ => {
+                return Err(ParseError::new(
+                    "'~' must be followed by '0' or '1'",
+                    offset + i,
+                ));
+            }
+        }
+    }
+    Ok(SegmentBuf::Map { key: Key::Str(key) })
+}
+
+/// Escapes `~` and `/` in a reference token.
+struct Escape<'a>(&'a mut String);
+
+impl<'a> fmt::Write for Escape<'a> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for ch in s.chars() {
+            match ch {
+                '~' => self.0.push_str("~0"),
+                '/' => self.0.push_str("~1"),
+                ch => self.0.push(ch),
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_array_index(token: &str) -> bool {
+    match token.as_bytes() {
+        [b'0'] => true,
+        [b'1'..=b'9', rest @ ..] => rest.iter().all(u8::is_ascii_digit),
+        _ => false,
+    }
+}
@@ -0,0 +1,147 @@
+use crate::callback::{Callback, Context};
+use crate::kind::Shape;
+use crate::path::{Path, PathBuf, SegmentBuf};
+use crate::value::Value;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::de;
+
+/// Something reported while replaying a buffered [`Value`], kept until it is
+/// known whether to pass it on.
+#[derive(Clone)]
+pub(crate) struct Event {
+    pub path: PathBuf,
+    pub context: Context<'static>,
+    pub variant: Option<String>,
+    pub kind: EventKind,
+}
+
+#[derive(Clone)]
+pub(crate) enum EventKind {
+    Ignored(Value),
+    Missing,
+    Duplicate,
+}
+
+impl Event {
+    // Reports this event to `callback`, at its path relative to `base`, which
+    // is where the replayed value was read from.
+    pub fn report<F, E>(self, callback: &mut F, base: &Path) -> Result<(), E>
+    where
+        F: Callback,
+        E: de::Error,
+    {
+        let segments = &self.path.segments()[base.depth()..];
+        let context = match &self.variant {
+            Some(variant) => self.context.with_variant(variant),
+            None => self.context,
+        };
+        with_path(base, segments, |path| match self.kind {
+            EventKind::Ignored(value) => {
+                if callback.captures_values() {
+                    callback.ignored_value(path, &context, value)
+                } else if callback.inspects_values() {
+                    let context = context.with_shape(Shape::of(&value));
+                    callback.ignored(path, &context)
+                } else {
+                    callback.ignored(path, &context)
+                }
+            }
+            EventKind::Missing => callback.missing(path, &context),
+            EventKind::Duplicate => callback.duplicate(path, &context),
+        })
+    }
+}
+
+/// Callback that records what is reported during a replay, capturing values
+/// inside of an `Allow` and tracking missing fields and duplicate keys only if
+/// the callback it records for does.
+pub(crate) struct Collect<'c> {
+    events: &'c mut Vec<Event>,
+    captures_allowed: bool,
+    tracks_missing: bool,
+    tracks_duplicates: bool,
+}
+
+impl<'c> Collect<'c> {
+    pub fn new<F>(events: &'c mut Vec<Event>, callback: &F) -> Self
+    where
+        F: Callback,
+    {
+        Collect {
+            events,
+            captures_allowed: callback.captures_values() && callback.captures_allowed(),
+            tracks_missing: callback.tracks_missing(),
+            tracks_duplicates: callback.tracks_duplicates(),
+        }
+    }
+
+    fn push(&mut self, path: &Path, context: &Context, kind: EventKind) {
+        self.events.push(Event {
+            path: path.to_owned(),
+            context: context.without_variant(),
+            variant: context.variant().map(String::from),
+            kind,
+        });
+    }
+}
+
+impl<'c> Callback for Collect<'c> {
+    fn ignored<E>(&mut self, path: Path, context: &Context) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        // Not called, because values are always captured.
+        let _ = (path, context);
+        Ok(())
+    }
+
+    fn captures_values(&self) -> bool {
+        true
+    }
+
+    fn ignored_value<E>(&mut self, path: Path, context: &Context, value: Value) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        self.push(&path, context, EventKind::Ignored(value));
+        Ok(())
+    }
+
+    fn captures_allowed(&self) -> bool {
+        self.captures_allowed
+    }
+
+    fn tracks_missing(&self) -> bool {
+        self.tracks_missing
+    }
+
+    fn missing<E>(&mut self, path: Path, context: &Context) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        self.push(&path, context, EventKind::Missing);
+        Ok(())
+    }
+
+    fn tracks_duplicates(&self) -> bool {
+        self.tracks_duplicates
+    }
+
+    fn duplicate<E>(&mut self, path: Path, context: &Context) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        self.push(&path, context, EventKind::Duplicate);
+        Ok(())
+    }
+}
+
+// Calls `f` with the path made of `parent` followed by `segments`, which has
+// to be built on the stack because each level borrows the one above it.
+fn with_path<R>(parent: &Path, segments: &[SegmentBuf], f: impl FnOnce(Path) -> R) -> R {
+    match segments {
+        [] => f(parent.clone()),
+        [first, rest @ ..] => with_path(&first.under(parent), rest, f),
+    }
+}
@@ -0,0 +1,979 @@
+//! Serializer adapter that writes ignored values back out, and the types it
+//! uses to serialize sequences, maps, structs and variants.
+
+use crate::extras::Extras;
+use crate::key::Key;
+use crate::path::Path;
+use crate::value::{Value, ValueSerializer};
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Display};
+use serde::ser::{self, Impossible, Serialize, SerializeMap as _};
+
+/// Serializer adapter that writes the values stored in an [`Extras`] back into
+/// the output, at the same paths from which they were captured.
+///
+/// Extra keys are appended after the fields of the struct, struct variant or
+/// entries of the map at their parent path. Because a struct's fields can only
+/// have static names, a struct that receives extra keys is serialized as a
+/// map instead, and a struct variant as a newtype variant containing a map.
+/// Values whose path ends in anything other than a map key are not written.
+///
+/// ```
+/// # use serde_derive::{Deserialize, Serialize};
+/// #
+/// #[derive(Deserialize, Serialize)]
+/// struct Config {
+///     name: String,
+/// }
+///
+/// let j = r#"{"name":"demo","added_in_v2":[1,2]}"#;
+/// let jd = &mut serde_json::Deserializer::from_str(j);
+/// let (mut config, extras): (Config, _) = serde_ignored::deserialize_preserving(jd)?;
+///
+/// config.name = "renamed".to_owned();
+///
+/// let mut out = Vec::new();
+/// let js = &mut serde_json::Serializer::new(&mut out);
+/// serde::Serialize::serialize(&config, serde_ignored::Serializer::new(js, &extras))?;
+/// assert_eq!(out, br#"{"name":"renamed","added_in_v2":[1,2]}"#);
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+pub struct Serializer<'a, 'b, S> {
+    ser: S,
+    extras: &'b Extras,
+    path: &'a Path<'a>,
+}
+
+impl<'b, S> Serializer<'static, 'b, S> {
+    pub fn new(ser: S, extras: &'b Extras) -> Self {
+        Serializer {
+            ser,
+            extras,
+            path: &Path::Root,
+        }
+    }
+}
+
+/// Forwarding impl that tracks the path of every value, and writes the extras
+/// of structs and maps after their own entries.
+impl<'a, 'b, S> ser::Serializer for Serializer<'a, 'b, S>
+where
+    S: ser::Serializer,
+{
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = SeqCompound<'a, 'b, S::SerializeSeq>;
+    type SerializeTuple = SeqCompound<'a, 'b, S::SerializeTuple>;
+    type SerializeTupleStruct = SeqCompound<'a, 'b, S::SerializeTupleStruct>;
+    type SerializeTupleVariant = VariantCompound<'a, 'b, S::SerializeTupleVariant>;
+    type SerializeMap = MapCompound<'a, 'b, S::SerializeMap>;
+    type SerializeStruct = StructCompound<'a, 'b, S>;
+    type SerializeStructVariant = StructVariantCompound<'a, 'b, S>;
+
+    fn serialize_bool(self, v: bool) -> Result<S::Ok, S::Error> {
+        self.ser.serialize_bool(v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<S::Ok, S::Error> {
+        self.ser.serialize_i8(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<S::Ok, S::Error> {
+        self.ser.serialize_i16(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<S::Ok, S::Error> {
+        self.ser.serialize_i32(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<S::Ok, S::Error> {
+        self.ser.serialize_i64(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<S::Ok, S::Error> {
+        self.ser.serialize_i128(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<S::Ok, S::Error> {
+        self.ser.serialize_u8(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<S::Ok, S::Error> {
+        self.ser.serialize_u16(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<S::Ok, S::Error> {
+        self.ser.serialize_u32(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<S::Ok, S::Error> {
+        self.ser.serialize_u64(v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<S::Ok, S::Error> {
+        self.ser.serialize_u128(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<S::Ok, S::Error> {
+        self.ser.serialize_f32(v)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<S::Ok, S::Error> {
+        self.ser.serialize_f64(v)
+    }
+
+    fn serialize_char(self, v: char) -> Result<S::Ok, S::Error> {
+        self.ser.serialize_char(v)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<S::Ok, S::Error> {
+        self.ser.serialize_str(v)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<S::Ok, S::Error> {
+        self.ser.serialize_bytes(v)
+    }
+
+    fn serialize_none(self) -> Result<S::Ok, S::Error> {
+        self.ser.serialize_none()
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<S::Ok, S::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let path = Path::Some { parent: self.path };
+        self.ser
+            .serialize_some(&Tracked::new(value, self.extras, path))
+    }
+
+    fn serialize_unit(self) -> Result<S::Ok, S::Error> {
+        self.ser.serialize_unit()
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<S::Ok, S::Error> {
+        self.ser.serialize_unit_struct(name)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<S::Ok, S::Error> {
+        self.ser
+            .serialize_unit_variant(name, variant_index, variant)
+    }
+
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<S::Ok, S::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let path = Path::NewtypeStruct { parent: self.path };
+        self.ser
+            .serialize_newtype_struct(name, &Tracked::new(value, self.extras, path))
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let parent = Path::Variant {
+            parent: self.path,
+            name: variant.to_owned(),
+        };
+        let path = Path::NewtypeVariant { parent: &parent };
+        let value = Tracked::new(value, self.extras, path);
+        self.ser
+            .serialize_newtype_variant(name, variant_index, variant, &value)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, S::Error> {
+        let delegate = self.ser.serialize_seq(len)?;
+        Ok(SeqCompound::new(delegate, self.extras, self.path))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, S::Error> {
+        let delegate = self.ser.serialize_tuple(len)?;
+        Ok(SeqCompound::new(delegate, self.extras, self.path))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, S::Error> {
+        let delegate = self.ser.serialize_tuple_struct(name, len)?;
+        Ok(SeqCompound::new(delegate, self.extras, self.path))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, S::Error> {
+        let delegate = self
+            .ser
+            .serialize_tuple_variant(name, variant_index, variant, len)?;
+        Ok(VariantCompound::new(
+            delegate,
+            self.extras,
+            self.path,
+            variant,
+        ))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, S::Error> {
+        let pending = self.extras.children(self.path);
+        let delegate = self.ser.serialize_map(len.map(|len| len + pending.len()))?;
+        Ok(MapCompound::new(delegate, self.extras, self.path, pending))
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, S::Error> {
+        let pending = self.extras.children(self.path);
+        if pending.is_empty() {
+            let delegate = self.ser.serialize_struct(name, len)?;
+            Ok(StructCompound::Struct(SeqCompound::new(
+                delegate,
+                self.extras,
+                self.path,
+            )))
+        } else {
+            let delegate = self.ser.serialize_map(Some(len + pending.len()))?;
+            Ok(StructCompound::Map(MapCompound::new(
+                delegate,
+                self.extras,
+                self.path,
+                pending,
+            )))
+        }
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, S::Error> {
+        let path = Path::Variant {
+            parent: self.path,
+            name: variant.to_owned(),
+        };
+        let pending = self.extras.children(&path);
+        let state = if pending.is_empty() {
+            let delegate = self
+                .ser
+                .serialize_struct_variant(name, variant_index, variant, len)?;
+            StructVariantState::Variant(VariantCompound::new(
+                delegate,
+                self.extras,
+                self.path,
+                variant,
+            ))
+        } else {
+            StructVariantState::Buffered(BufferedVariant {
+                ser: self.ser,
+                name,
+                variant_index,
+                variant,
+                extras: self.extras,
+                path,
+                fields: Vec::with_capacity(len),
+                pending,
+            })
+        };
+        Ok(StructVariantCompound { state })
+    }
+
+    fn collect_str<T>(self, value: &T) -> Result<S::Ok, S::Error>
+    where
+        T: ?Sized + Display,
+    {
+        self.ser.collect_str(value)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.ser.is_human_readable()
+    }
+}
+
+/// Value to be serialized at a known path.
+struct Tracked<'a, 'b, T: ?Sized> {
+    value: &'a T,
+    extras: &'b Extras,
+    path: Path<'a>,
+}
+
+impl<'a, 'b, T: ?Sized> Tracked<'a, 'b, T> {
+    fn new(value: &'a T, extras: &'b Extras, path: Path<'a>) -> Self {
+        Tracked {
+            value,
+            extras,
+            path,
+        }
+    }
+}
+
+impl<'a, 'b, T> Serialize for Tracked<'a, 'b, T>
+where
+    T: ?Sized + Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        self.value.serialize(Serializer {
+            ser: serializer,
+            extras: self.extras,
+            path: &self.path,
+        })
+    }
+}
+
+/// Seq, tuple and struct serializer that tracks the path of its elements.
+pub struct SeqCompound<'a, 'b, X> {
+    delegate: X,
+    extras: &'b Extras,
+    path: &'a Path<'a>,
+    index: usize,
+}
+
+impl<'a, 'b, X> SeqCompound<'a, 'b, X> {
+    fn new(delegate: X, extras: &'b Extras, path: &'a Path<'a>) -> Self {
+        SeqCompound {
+            delegate,
+            extras,
+            path,
+            index: 0,
+        }
+    }
+
+    fn next_path(&mut self) -> Path<'a> {
+        let path = Path::Seq {
+            parent: self.path,
+            index: self.index,
+        };
+        self.index += 1;
+        path
+    }
+}
+
+impl<'a, 'b, X> ser::SerializeSeq for SeqCompound<'a, 'b, X>
+where
+    X: ser::SerializeSeq,
+{
+    type Ok = X::Ok;
+    type Error = X::Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), X::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let path = self.next_path();
+        self.delegate
+            .serialize_element(&Tracked::new(value, self.extras, path))
+    }
+
+    fn end(self) -> Result<X::Ok, X::Error> {
+        self.delegate.end()
+    }
+}
+
+impl<'a, 'b, X> ser::SerializeTuple for SeqCompound<'a, 'b, X>
+where
+    X: ser::SerializeTuple,
+{
+    type Ok = X::Ok;
+    type Error = X::Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), X::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let path = self.next_path();
+        self.delegate
+            .serialize_element(&Tracked::new(value, self.extras, path))
+    }
+
+    fn end(self) -> Result<X::Ok, X::Error> {
+        self.delegate.end()
+    }
+}
+
+impl<'a, 'b, X> ser::SerializeTupleStruct for SeqCompound<'a, 'b, X>
+where
+    X: ser::SerializeTupleStruct,
+{
+    type Ok = X::Ok;
+    type Error = X::Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), X::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let path = self.next_path();
+        self.delegate
+            .serialize_field(&Tracked::new(value, self.extras, path))
+    }
+
+    fn end(self) -> Result<X::Ok, X::Error> {
+        self.delegate.end()
+    }
+}
+
+impl<'a, 'b, X> ser::SerializeStruct for SeqCompound<'a, 'b, X>
+where
+    X: ser::SerializeStruct,
+{
+    type Ok = X::Ok;
+    type Error = X::Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), X::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let path = Path::Map {
+            parent: self.path,
+            key: Key::from(key),
+        };
+        self.delegate
+            .serialize_field(key, &Tracked::new(value, self.extras, path))
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), X::Error> {
+        self.delegate.skip_field(key)
+    }
+
+    fn end(self) -> Result<X::Ok, X::Error> {
+        self.delegate.end()
+    }
+}
+
+/// Tuple variant and struct variant serializer that tracks the path through
+/// the variant.
+pub struct VariantCompound<'a, 'b, X> {
+    delegate: X,
+    extras: &'b Extras,
+    path: Path<'a>,
+    index: usize,
+}
+
+impl<'a, 'b, X> VariantCompound<'a, 'b, X> {
+    fn new(delegate: X, extras: &'b Extras, parent: &'a Path<'a>, variant: &str) -> Self {
+        VariantCompound {
+            delegate,
+            extras,
+            path: Path::Variant {
+                parent,
+                name: variant.to_owned(),
+            },
+            index: 0,
+        }
+    }
+}
+
+impl<'a, 'b, X> ser::SerializeTupleVariant for VariantCompound<'a, 'b, X>
+where
+    X: ser::SerializeTupleVariant,
+{
+    type Ok = X::Ok;
+    type Error = X::Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), X::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let path = Path::Seq {
+            parent: &self.path,
+            index: self.index,
+        };
+        self.index += 1;
+        self.delegate
+            .serialize_field(&Tracked::new(value, self.extras, path))
+    }
+
+    fn end(self) -> Result<X::Ok, X::Error> {
+        self.delegate.end()
+    }
+}
+
+impl<'a, 'b, X> ser::SerializeStructVariant for VariantCompound<'a, 'b, X>
+where
+    X: ser::SerializeStructVariant,
+{
+    type Ok = X::Ok;
+    type Error = X::Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), X::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let path = Path::Map {
+            parent: &self.path,
+            key: Key::from(key),
+        };
+        self.delegate
+            .serialize_field(key, &Tracked::new(value, self.extras, path))
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), X::Error> {
+        self.delegate.skip_field(key)
+    }
+
+    fn end(self) -> Result<X::Ok, X::Error> {
+        self.delegate.end()
+    }
+}
+
+/// Map serializer that tracks the path of its values and appends the extras
+/// at its path before ending.
+pub struct MapCompound<'a, 'b, X> {
+    delegate: X,
+    extras: &'b Extras,
+    path: &'a Path<'a>,
+    key: Option<Key>,
+    pending: Vec<(&'b Key, &'b Value)>,
+}
+
+impl<'a, 'b, X> MapCompound<'a, 'b, X> {
+    fn new(
+        delegate: X,
+        extras: &'b Extras,
+        path: &'a Path<'a>,
+        pending: Vec<(&'b Key, &'b Value)>,
+    ) -> Self {
+        MapCompound {
+            delegate,
+            extras,
+            path,
+            key: None,
+            pending,
+        }
+    }
+
+    // Same fallback as for deserialization of a key that could not be
+    // captured.
+    fn key(&mut self) -> Key {
+        self.key
+            .take()
+            .unwrap_or_else(|| Key::Other(String::from("?")))
+    }
+}
+
+impl<'a, 'b, X> MapCompound<'a, 'b, X>
+where
+    X: ser::SerializeMap,
+{
+    fn end_with_extras(mut self) -> Result<X::Ok, X::Error> {
+        for (key, value) in self.pending {
+            self.delegate.serialize_entry(key, value)?;
+        }
+        self.delegate.end()
+    }
+}
+
+impl<'a, 'b, X> ser::SerializeMap for MapCompound<'a, 'b, X>
+where
+    X: ser::SerializeMap,
+{
+    type Ok = X::Ok;
+    type Error = X::Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), X::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.key = key.serialize(CaptureKey).ok();
+        self.delegate.serialize_key(key)
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), X::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let path = Path::Map {
+            parent: self.path,
+            key: self.key(),
+        };
+        self.delegate
+            .serialize_value(&Tracked::new(value, self.extras, path))
+    }
+
+    fn end(self) -> Result<X::Ok, X::Error> {
+        self.end_with_extras()
+    }
+}
+
+impl<'a, 'b, X> ser::SerializeStruct for MapCompound<'a, 'b, X>
+where
+    X: ser::SerializeMap,
+{
+    type Ok = X::Ok;
+    type Error = X::Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), X::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let path = Path::Map {
+            parent: self.path,
+            key: Key::from(key),
+        };
+        self.delegate
+            .serialize_entry(key, &Tracked::new(value, self.extras, path))
+    }
+
+    fn end(self) -> Result<X::Ok, X::Error> {
+        self.end_with_extras()
+    }
+}
+
+/// Struct serializer, which turns into a map serializer if there are extras
+/// to write into the struct.
+pub enum StructCompound<'a, 'b, S: ser::Serializer> {
+    Struct(SeqCompound<'a, 'b, S::SerializeStruct>),
+    Map(MapCompound<'a, 'b, S::SerializeMap>),
+}
+
+impl<'a, 'b, S> ser::SerializeStruct for StructCompound<'a, 'b, S>
+where
+    S: ser::Serializer,
+{
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), S::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        match self {
+            StructCompound::Struct(compound) => compound.serialize_field(key, value),
+            StructCompound::Map(compound) => compound.serialize_field(key, value),
+        }
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), S::Error> {
+        match self {
+            StructCompound::Struct(compound) => compound.skip_field(key),
+            StructCompound::Map(compound) => compound.skip_field(key),
+        }
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        match self {
+            StructCompound::Struct(compound) => ser::SerializeStruct::end(compound),
+            StructCompound::Map(compound) => ser::SerializeStruct::end(compound),
+        }
+    }
+}
+
+/// Struct variant serializer, which buffers the fields and writes the variant
+/// as a newtype variant containing a map if there are extras to write into
+/// the variant.
+pub struct StructVariantCompound<'a, 'b, S: ser::Serializer> {
+    state: StructVariantState<'a, 'b, S>,
+}
+
+enum StructVariantState<'a, 'b, S: ser::Serializer> {
+    Variant(VariantCompound<'a, 'b, S::SerializeStructVariant>),
+    Buffered(BufferedVariant<'a, 'b, S>),
+}
+
+struct BufferedVariant<'a, 'b, S> {
+    ser: S,
+    name: &'static str,
+    variant_index: u32,
+    variant: &'static str,
+    extras: &'b Extras,
+    path: Path<'a>,
+    fields: Vec<(&'static str, Value)>,
+    pending: Vec<(&'b Key, &'b Value)>,
+}
+
+impl<'a, 'b, S> ser::SerializeStructVariant for StructVariantCompound<'a, 'b, S>
+where
+    S: ser::Serializer,
+{
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), S::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        match &mut self.state {
+            StructVariantState::Variant(compound) => compound.serialize_field(key, value),
+            StructVariantState::Buffered(buffered) => {
+                let path = Path::Map {
+                    parent: &buffered.path,
+                    key: Key::from(key),
+                };
+                let value =
+                    Tracked::new(value, buffered.extras, path).serialize(ValueSerializer::new())?;
+                buffered.fields.push((key, value));
+                Ok(())
+            }
+        }
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), S::Error> {
+        match &mut self.state {
+            StructVariantState::Variant(compound) => compound.skip_field(key),
+            StructVariantState::Buffered(_) => Ok(()),
+        }
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        match self.state {
+            StructVariantState::Variant(compound) => ser::SerializeStructVariant::end(compound),
+            StructVariantState::Buffered(buffered) => {
+                let content = VariantContent {
+                    fields: &buffered.fields,
+                    pending: &buffered.pending,
+                };
+                buffered.ser.serialize_newtype_variant(
+                    buffered.name,
+                    buffered.variant_index,
+                    buffered.variant,
+                    &content,
+                )
+            }
+        }
+    }
+}
+
+/// Fields of a buffered struct variant followed by its extras.
+struct VariantContent<'c, 'b> {
+    fields: &'c [(&'static str, Value)],
+    pending: &'c [(&'b Key, &'b Value)],
+}
+
+impl<'c, 'b> Serialize for VariantContent<'c, 'b> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        let len = self.fields.len() + self.pending.len();
+        let mut map = serializer.serialize_map(Some(len))?;
+        for (key, value) in self.fields {
+            map.serialize_entry(key, value)?;
+        }
+        for (key, value) in self.pending {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+/// Serializer that turns a map key into a `Key`, in the same way that the
+/// deserializer captures keys, so that the paths of values match.
+struct CaptureKey;
+
+/// A key that is a sequence, map or data-carrying enum variant.
+#[derive(Debug)]
+struct NotAKey;
+
+impl Display for NotAKey {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("not a key")
+    }
+}
+
+impl ser::StdError for NotAKey {}
+
+impl ser::Error for NotAKey {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: Display,
+    {
+        let _ = msg;
+        NotAKey
+    }
+}
+
+impl ser::Serializer for CaptureKey {
+    type Ok = Key;
+    type Error = NotAKey;
+    type SerializeSeq = Impossible<Key, NotAKey>;
+    type SerializeTuple = Impossible<Key, NotAKey>;
+    type SerializeTupleStruct = Impossible<Key, NotAKey>;
+    type SerializeTupleVariant = Impossible<Key, NotAKey>;
+    type SerializeMap = Impossible<Key, NotAKey>;
+    type SerializeStruct = Impossible<Key, NotAKey>;
+    type SerializeStructVariant = Impossible<Key, NotAKey>;
+
+    fn serialize_bool(self, v: bool) -> Result<Key, NotAKey> {
+        Ok(Key::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Key, NotAKey> {
+        Ok(Key::Int(i128::from(v)))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Key, NotAKey> {
+        Ok(Key::Int(i128::from(v)))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Key, NotAKey> {
+        Ok(Key::Int(i128::from(v)))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Key, NotAKey> {
+        Ok(Key::Int(i128::from(v)))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Key, NotAKey> {
+        Ok(Key::Int(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Key, NotAKey> {
+        Ok(Key::Uint(u128::from(v)))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Key, NotAKey> {
+        Ok(Key::Uint(u128::from(v)))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Key, NotAKey> {
+        Ok(Key::Uint(u128::from(v)))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Key, NotAKey> {
+        Ok(Key::Uint(u128::from(v)))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Key, NotAKey> {
+        Ok(Key::Uint(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Key, NotAKey> {
+        Ok(Key::Float(f64::from(v)))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Key, NotAKey> {
+        Ok(Key::Float(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Key, NotAKey> {
+        Ok(Key::Char(v))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Key, NotAKey> {
+        Ok(Key::from(v))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Key, NotAKey> {
+        Ok(Key::Bytes(v.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<Key, NotAKey> {
+        Ok(Key::Unit)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Key, NotAKey>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Key, NotAKey> {
+        Ok(Key::Unit)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Key, NotAKey> {
+        Ok(Key::Unit)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Key, NotAKey> {
+        Ok(Key::from(variant))
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Key, NotAKey>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Key, NotAKey>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(NotAKey)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, NotAKey> {
+        Err(NotAKey)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, NotAKey> {
+        Err(NotAKey)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, NotAKey> {
+        Err(NotAKey)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, NotAKey> {
+        Err(NotAKey)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, NotAKey> {
+        Err(NotAKey)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, NotAKey> {
+        Err(NotAKey)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, NotAKey> {
+        Err(NotAKey)
+    }
+}
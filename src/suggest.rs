@@ -0,0 +1,55 @@
+use alloc::vec::Vec;
+
+/// Picks the candidate closest to `key`, if any is close enough to be a
+/// plausible typo.
+///
+/// Keys are compared case-insensitively and with `-` and `_` treated as the
+/// same character, so `Dry-Run` matches `dry_run` exactly. Otherwise the
+/// candidate with the smallest edit distance wins, with ties going to the one
+/// listed first, as long as the distance is at most a third of the length of
+/// the key (but at least 1). Swapping two adjacent characters counts as a
+/// single edit, so `nmae` is a typo of `name`.
+pub(crate) fn closest<'a>(key: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let key = normalize(key);
+    let max_distance = (key.len() / 3).max(1);
+    let mut best = None;
+    for &candidate in candidates {
+        let distance = edit_distance(&key, &normalize(candidate));
+        if distance <= max_distance && best.map_or(true, |(_, best)| distance < best) {
+            best = Some((candidate, distance));
+        }
+    }
+    best.map(|(candidate, _)| candidate)
+}
+
+fn normalize(s: &str) -> Vec<char> {
+    s.chars()
+        .map(|ch| match ch {
+            '-' => '_',
+            ch => ch.to_ascii_lowercase(),
+        })
+        .collect()
+}
+
+// Levenshtein distance extended with transpositions of adjacent characters
+// (optimal string alignment distance).
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    // Rows i-1 and i-2 of the dynamic programming table, where row[j] is the
+    // distance between the first i chars of `a` and the first j chars of `b`.
+    let mut prev2 = Vec::new();
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    for i in 1..=a.len() {
+        let mut row = Vec::with_capacity(b.len() + 1);
+        row.push(i);
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (prev[j - 1] + cost).min(prev[j] + 1).min(row[j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(prev2[j - 2] + 1);
+            }
+            row.push(distance);
+        }
+        prev2 = core::mem::replace(&mut prev, row);
+    }
+    prev[b.len()]
+}
@@ -0,0 +1,628 @@
+use crate::callback::{Callback, Context};
+use crate::key::Key;
+use crate::path::Path;
+use crate::replay::{Collect, Event};
+use crate::value::{Value, ValueDeserializer};
+use crate::Deserializer;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+use serde::de::{
+    self, Deserialize, DeserializeSeed, Deserializer as _, EnumAccess, IgnoredAny,
+    IntoDeserializer, MapAccess, SeqAccess, VariantAccess, Visitor,
+};
+use serde::forward_to_deserialize_any;
+
+/// Name of the newtype struct through which `Tagged` asks the
+/// [`Deserializer`] to replay the input to the enum.
+pub(crate) const NAME: &str = "serde_ignored::Tagged";
+
+const INTERNAL: &str = "internal";
+const ADJACENT: &str = "adjacent";
+const UNTAGGED: &str = "untagged";
+
+/// How an enum wrapped in [`Tagged`] is represented in the input, with the
+/// same meaning as serde's [enum representations].
+///
+/// [enum representations]: https://serde.rs/enum-representations.html
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Tagging {
+    /// Like `#[serde(tag = "...")]`.
+    Internal { tag: &'static str },
+    /// Like `#[serde(tag = "...", content = "...")]`.
+    Adjacent {
+        tag: &'static str,
+        content: &'static str,
+    },
+    /// Like `#[serde(untagged)]`.
+    Untagged,
+}
+
+/// An enum that can be wrapped in [`Tagged`].
+pub trait TaggedEnum {
+    const TAGGING: Tagging;
+}
+
+/// Deserializes an internally tagged, adjacently tagged or untagged enum, for
+/// use in place of serde's `tag`, `content` and `untagged` attributes.
+///
+/// Serde buffers the input of an enum with one of those attributes before
+/// choosing the variant, and deserializes the variant from the buffer without
+/// going through the [`Deserializer`], so keys that are ignored inside of it
+/// never get reported. `Tagged<T>` instead reads the input into a [`Value`],
+/// picks the variant itself according to [`T::TAGGING`], and deserializes `T`
+/// from it as an ordinary externally tagged enum. Keys that the variant
+/// ignores are reported at the same paths they have in the input.
+///
+/// ```
+/// # use serde_derive::Deserialize;
+/// #
+/// use serde_ignored::{Tagged, TaggedEnum, Tagging};
+///
+/// // No #[serde(tag = "type")] here. Tagged takes care of that.
+/// #[derive(Deserialize)]
+/// enum Plugin {
+///     #[serde(rename = "http")]
+///     Http { url: String },
+///     #[serde(rename = "file")]
+///     File { path: String },
+/// }
+///
+/// impl TaggedEnum for Plugin {
+///     const TAGGING: Tagging = Tagging::Internal { tag: "type" };
+/// }
+///
+/// let j = r#"[{"type": "http", "url": "https://example.com", "timeout": 5}]"#;
+/// let jd = &mut serde_json::Deserializer::from_str(j);
+/// let mut unused = Vec::new();
+///
+/// let plugins: Vec<Tagged<Plugin>> =
+///     serde_ignored::deserialize(jd, |path| unused.push(path.to_string()))?;
+///
+/// assert!(matches!(plugins[0].0, Plugin::Http { .. }));
+/// assert_eq!(unused, ["0.timeout"]);
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+///
+/// Because `T` is deserialized from a `Value`, it cannot borrow from the
+/// input.
+///
+/// [`T::TAGGING`]: TaggedEnum::TAGGING
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Tagged<T>(pub T);
+
+// The tagging of T as a static list of strings, which is the form in which it
+// can be passed through deserialize_struct to Attempts.
+struct Fields<T>(PhantomData<T>);
+
+impl<T> Fields<T>
+where
+    T: TaggedEnum,
+{
+    const FIELDS: &'static [&'static str] = match T::TAGGING {
+        Tagging::Internal { tag } => &[INTERNAL, tag],
+        Tagging::Adjacent { tag, content } => &[ADJACENT, tag, content],
+        Tagging::Untagged => &[UNTAGGED],
+    };
+}
+
+impl Tagging {
+    // The inverse of Fields::FIELDS, together with the keys that the tagging
+    // uses.
+    fn from_fields(fields: &'static [&'static str]) -> Option<(Self, &'static [&'static str])> {
+        let tagging = match *fields {
+            [INTERNAL, tag] => Tagging::Internal { tag },
+            [ADJACENT, tag, content] => Tagging::Adjacent { tag, content },
+            [UNTAGGED] => Tagging::Untagged,
+            _ => return None,
+        };
+        Some((tagging, &fields[1..]))
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Tagged<T>
+where
+    T: Deserialize<'de> + TaggedEnum,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(NAME, TaggedVisitor(PhantomData))
+    }
+}
+
+struct TaggedVisitor<T>(PhantomData<T>);
+
+impl<'de, T> Visitor<'de> for TaggedVisitor<T>
+where
+    T: Deserialize<'de> + TaggedEnum,
+{
+    type Value = Tagged<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any value")
+    }
+
+    // Reached without serde_ignored's Deserializer, from formats that handle
+    // newtype structs as their content. The enum is picked out the same way,
+    // with nowhere to report ignored values to.
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let value = Value::deserialize(deserializer)?;
+        let mut callback = |_: Path| {};
+        let attempts = Attempts::new(value, &mut callback, Path::Root, Context::default());
+        self.visit_seq(attempts)
+    }
+
+    // Reached through serde_ignored's Deserializer. The first element tells it
+    // the tagging, and each of the following ones is an attempt to
+    // deserialize T, of which only untagged enums make more than one.
+    fn visit_seq<S>(self, mut seq: S) -> Result<Self::Value, S::Error>
+    where
+        S: SeqAccess<'de>,
+    {
+        seq.next_element_seed(TaggingSeed::<T>(PhantomData))?;
+        let mut error = None;
+        loop {
+            match seq.next_element() {
+                Ok(Some(value)) => {
+                    // Lets the deserializer report what the attempt ignored.
+                    seq.next_element::<IgnoredAny>()?;
+                    return Ok(Tagged(value));
+                }
+                Ok(None) => {
+                    return Err(error.unwrap_or_else(|| de::Error::invalid_length(0, &self)));
+                }
+                Err(err) => error = Some(err),
+            }
+        }
+    }
+}
+
+struct TaggingSeed<T>(PhantomData<T>);
+
+impl<'de, T> DeserializeSeed<'de> for TaggingSeed<T>
+where
+    T: TaggedEnum,
+{
+    type Value = IgnoredAny;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<IgnoredAny, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_struct(NAME, Fields::<T>::FIELDS, IgnoredAny)
+    }
+}
+
+/// Seq access that replays the same value to each attempt to deserialize the
+/// enum in a `Tagged`, then reports what the successful one ignored, along
+/// with its missing fields and duplicate keys.
+pub(crate) struct Attempts<'a, 'b, F: 'b, E> {
+    value: Value,
+    callback: &'b mut F,
+    path: Path<'a>,
+    context: Context<'static>,
+    tagging: Option<(Tagging, &'static [&'static str])>,
+    attempt: usize,
+    exhausted: bool,
+    // What the successful attempt reported, until it is passed on.
+    events: Option<Vec<Event>>,
+    marker: PhantomData<E>,
+}
+
+impl<'a, 'b, F, E> Attempts<'a, 'b, F, E> {
+    pub(crate) fn new(
+        value: Value,
+        callback: &'b mut F,
+        path: Path<'a>,
+        context: Context<'static>,
+    ) -> Self {
+        Attempts {
+            value,
+            callback,
+            path,
+            context,
+            tagging: None,
+            attempt: 0,
+            exhausted: false,
+            events: None,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, 'b, 'de, F, E> SeqAccess<'de> for Attempts<'a, 'b, F, E>
+where
+    F: Callback,
+    E: de::Error,
+{
+    type Error = E;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, E>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let Some((tagging, keys)) = self.tagging else {
+            let receiver = TaggingReceiver {
+                tagging: &mut self.tagging,
+                marker: PhantomData,
+            };
+            return seed.deserialize(receiver).map(Some);
+        };
+        if let Some(events) = self.events.take() {
+            for event in events {
+                event.report(self.callback, &self.path)?;
+            }
+            return Ok(None);
+        }
+        if self.exhausted {
+            return Ok(None);
+        }
+        let mut events = Vec::new();
+        let dispatch = Dispatch {
+            value: self.value.clone(),
+            tagging,
+            keys,
+            attempt: self.attempt,
+            exhausted: &mut self.exhausted,
+            callback: Collect::new(&mut events, self.callback),
+            path: self.path.clone(),
+            context: self.context,
+            marker: PhantomData,
+        };
+        self.attempt += 1;
+        let value = seed.deserialize(dispatch)?;
+        self.events = Some(events);
+        Ok(Some(value))
+    }
+}
+
+/// Deserializer that only accepts the tagging from `TaggingSeed`.
+struct TaggingReceiver<'m, E> {
+    tagging: &'m mut Option<(Tagging, &'static [&'static str])>,
+    marker: PhantomData<E>,
+}
+
+impl<'m, 'de, E> de::Deserializer<'de> for TaggingReceiver<'m, E>
+where
+    E: de::Error,
+{
+    type Error = E;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, E>
+    where
+        V: Visitor<'de>,
+    {
+        let _ = visitor;
+        Err(E::custom("expected the tagging of a serde_ignored::Tagged"))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, E>
+    where
+        V: Visitor<'de>,
+    {
+        match Tagging::from_fields(fields) {
+            Some(tagging) if name == NAME => {
+                *self.tagging = Some(tagging);
+                visitor.visit_unit()
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+/// Deserializer for one attempt, which picks the variant of the enum out of
+/// the value according to the tagging.
+struct Dispatch<'a, 'b, 'c, E> {
+    value: Value,
+    tagging: Tagging,
+    // The tag and content keys, which are expected alongside the variant.
+    keys: &'static [&'static str],
+    attempt: usize,
+    exhausted: &'b mut bool,
+    callback: Collect<'c>,
+    path: Path<'a>,
+    context: Context<'static>,
+    marker: PhantomData<E>,
+}
+
+impl<'a, 'b, 'c, 'de, E> de::Deserializer<'de> for Dispatch<'a, 'b, 'c, E>
+where
+    E: de::Error,
+{
+    type Error = E;
+
+    // Not an enum after all, so there is nothing to pick out.
+    fn deserialize_any<V>(mut self, visitor: V) -> Result<V::Value, E>
+    where
+        V: Visitor<'de>,
+    {
+        *self.exhausted = true;
+        Deserializer {
+            de: self.value.into_deserializer(),
+            callback: &mut self.callback,
+            path: self.path,
+            context: self.context,
+            skipped: None,
+        }
+        .deserialize_any(visitor)
+    }
+
+    fn deserialize_enum<V>(
+        mut self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, E>
+    where
+        V: Visitor<'de>,
+    {
+        let context = self.context.with_container(name);
+        let (variant, value, key) = match self.tagging {
+            Tagging::Internal { tag } => {
+                *self.exhausted = true;
+                let mut entries = into_entries(self.value)?;
+                let variant = take(&mut entries, tag).ok_or_else(|| E::missing_field(tag))?;
+                (variant, Value::Map(entries), None)
+            }
+            Tagging::Adjacent { tag, content } => {
+                *self.exhausted = true;
+                let mut entries = into_entries(self.value)?;
+                let variant = take(&mut entries, tag).ok_or_else(|| E::missing_field(tag))?;
+                let value = take(&mut entries, content).unwrap_or(Value::Unit);
+                Deserializer {
+                    de: Value::Map(entries).into_deserializer(),
+                    callback: &mut self.callback,
+                    path: self.path.clone(),
+                    context,
+                    skipped: None,
+                }
+                .deserialize_struct(name, self.keys, Drain)?;
+                (variant, value, Some(content))
+            }
+            Tagging::Untagged => {
+                let Some(variant) = variants.get(self.attempt) else {
+                    *self.exhausted = true;
+                    return Err(E::custom(format_args!(
+                        "data did not match any variant of untagged enum {}",
+                        name,
+                    )));
+                };
+                (Value::Str(String::from(*variant)), self.value, None)
+            }
+        };
+        visitor.visit_enum(VariantReplay {
+            variant,
+            content: ContentReplay {
+                value,
+                key,
+                internal: matches!(self.tagging, Tagging::Internal { .. }),
+                name,
+                callback: self.callback,
+                path: self.path,
+                context,
+                marker: PhantomData,
+            },
+        })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+fn into_entries<E>(value: Value) -> Result<Vec<(Value, Value)>, E>
+where
+    E: de::Error,
+{
+    match value {
+        Value::Map(entries) => Ok(entries),
+        value => Err(E::invalid_type(value.unexpected(), &"map")),
+    }
+}
+
+// Removes the entry with the given string key.
+fn take(entries: &mut Vec<(Value, Value)>, key: &str) -> Option<Value> {
+    let index = entries
+        .iter()
+        .position(|(k, _)| matches!(k, Value::Str(k) if k == key))?;
+    Some(entries.remove(index).1)
+}
+
+/// The variant picked out by `Dispatch`.
+struct VariantReplay<'a, 'c, E> {
+    variant: Value,
+    content: ContentReplay<'a, 'c, E>,
+}
+
+impl<'a, 'c, 'de, E> EnumAccess<'de> for VariantReplay<'a, 'c, E>
+where
+    E: de::Error,
+{
+    type Error = E;
+    type Variant = ContentReplay<'a, 'c, E>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), E>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((variant, self.content))
+    }
+}
+
+/// Content of the variant picked out by `Dispatch`, which is tracked at the
+/// path of the enum itself, or of its content key if adjacently tagged.
+struct ContentReplay<'a, 'c, E> {
+    value: Value,
+    key: Option<&'static str>,
+    // Internally tagged unit variants accept, and ignore, other keys.
+    internal: bool,
+    name: &'static str,
+    callback: Collect<'c>,
+    path: Path<'a>,
+    context: Context<'static>,
+    marker: PhantomData<E>,
+}
+
+impl<'a, 'c, E> ContentReplay<'a, 'c, E>
+where
+    E: de::Error,
+{
+    fn replay<R>(
+        mut self,
+        f: impl FnOnce(Deserializer<ValueDeserializer<E>, Collect<'c>>) -> R,
+    ) -> R {
+        let de = self.value.into_deserializer();
+        let callback = &mut self.callback;
+        match self.key {
+            None => f(Deserializer {
+                de,
+                callback,
+                path: self.path,
+                context: self.context,
+                skipped: None,
+            }),
+            Some(key) => f(Deserializer {
+                de,
+                callback,
+                path: Path::Map {
+                    parent: &self.path,
+                    key: Key::from(key),
+                },
+                context: self.context,
+                skipped: None,
+            }),
+        }
+    }
+}
+
+impl<'a, 'c, 'de, E> VariantAccess<'de> for ContentReplay<'a, 'c, E>
+where
+    E: de::Error,
+{
+    type Error = E;
+
+    fn unit_variant(self) -> Result<(), E> {
+        match self.value {
+            Value::Map(_) if self.internal => {
+                let name = self.name;
+                self.replay(|de| de.deserialize_struct(name, &[], Drain))
+            }
+            Value::Unit | Value::None => Ok(()),
+            ref value => Err(E::invalid_type(value.unexpected(), &"unit variant")),
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, E>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.replay(|de| seed.deserialize(de))
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, E>
+    where
+        V: Visitor<'de>,
+    {
+        self.replay(|de| de.deserialize_tuple(len, visitor))
+    }
+
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value, E>
+    where
+        V: Visitor<'de>,
+    {
+        let name = self.name;
+        self.replay(|de| de.deserialize_struct(name, fields, visitor))
+    }
+}
+
+/// Visitor that skips every entry of a map, so that the deserializer reports
+/// each of them as ignored.
+struct Drain;
+
+impl<'de> Visitor<'de> for Drain {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<(), A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        while map.next_key::<AnyKey>()?.is_some() {
+            map.next_value::<IgnoredAny>()?;
+        }
+        Ok(())
+    }
+}
+
+/// Map key that accepts any scalar. Unlike `Value`, it never visits a nested
+/// map or sequence, which would make the key deserializer types recurse
+/// without bound.
+struct AnyKey;
+
+impl<'de> Deserialize<'de> for AnyKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_identifier(AnyKey)
+    }
+}
+
+impl<'de> Visitor<'de> for AnyKey {
+    type Value = AnyKey;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map key")
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<AnyKey, E> {
+        Ok(AnyKey)
+    }
+
+    fn visit_i64<E>(self, _: i64) -> Result<AnyKey, E> {
+        Ok(AnyKey)
+    }
+
+    fn visit_u64<E>(self, _: u64) -> Result<AnyKey, E> {
+        Ok(AnyKey)
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<AnyKey, E> {
+        Ok(AnyKey)
+    }
+
+    fn visit_str<E>(self, _: &str) -> Result<AnyKey, E> {
+        Ok(AnyKey)
+    }
+
+    fn visit_bytes<E>(self, _: &[u8]) -> Result<AnyKey, E> {
+        Ok(AnyKey)
+    }
+
+    fn visit_unit<E>(self) -> Result<AnyKey, E> {
+        Ok(AnyKey)
+    }
+}
@@ -0,0 +1,833 @@
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display};
+use core::marker::PhantomData;
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{
+    self, Deserialize, DeserializeSeed, Deserializer, EnumAccess, IntoDeserializer, MapAccess,
+    SeqAccess, Unexpected, VariantAccess, Visitor,
+};
+use serde::forward_to_deserialize_any;
+use serde::ser::{self, Serialize, Serializer};
+
+/// Format-agnostic copy of an ignored value, as passed to
+/// [`deserialize_with_values`](crate::deserialize_with_values).
+///
+/// Integers are `Int` or `Uint` depending on whether the input reported them
+/// through a signed or unsigned visitor method, the same as for [`Key`].
+///
+/// [`Key`]: crate::Key
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Unit,
+    Bool(bool),
+    Int(i128),
+    Uint(u128),
+    Float(f64),
+    Char(char),
+    Str(String),
+    Bytes(Vec<u8>),
+    None,
+    Some(Box<Value>),
+    Newtype(Box<Value>),
+    Seq(Vec<Value>),
+    /// Entries in input order. An enum is recorded as a map with a single
+    /// entry from the variant to its content.
+    Map(Vec<(Value, Value)>),
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+/// Serializes the value back in the shape it was deserialized from, except
+/// that `Newtype` is serialized as just its content, because the name of the
+/// newtype struct is not known.
+impl Serialize for Value {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Value::Unit => serializer.serialize_unit(),
+            Value::Bool(b) => serializer.serialize_bool(*b),
+            Value::Int(i) => match i64::try_from(*i) {
+                Ok(i) => serializer.serialize_i64(i),
+                Err(_) => serializer.serialize_i128(*i),
+            },
+            Value::Uint(u) => match u64::try_from(*u) {
+                Ok(u) => serializer.serialize_u64(u),
+                Err(_) => serializer.serialize_u128(*u),
+            },
+            Value::Float(f) => serializer.serialize_f64(*f),
+            Value::Char(c) => serializer.serialize_char(*c),
+            Value::Str(s) => serializer.serialize_str(s),
+            Value::Bytes(bytes) => serializer.serialize_bytes(bytes),
+            Value::None => serializer.serialize_none(),
+            Value::Some(v) => serializer.serialize_some(v),
+            Value::Newtype(v) => v.serialize(serializer),
+            Value::Seq(elements) => serializer.collect_seq(elements),
+            Value::Map(entries) => {
+                serializer.collect_map(entries.iter().map(|(key, value)| (key, value)))
+            }
+        }
+    }
+}
+
+/// Replays the value, so that it can be deserialized again as some other
+/// type.
+impl<'de, E> IntoDeserializer<'de, E> for Value
+where
+    E: de::Error,
+{
+    type Deserializer = ValueDeserializer<E>;
+
+    fn into_deserializer(self) -> ValueDeserializer<E> {
+        ValueDeserializer {
+            value: self,
+            marker: PhantomData,
+        }
+    }
+}
+
+/// Deserializer over a [`Value`], created by its `IntoDeserializer` impl.
+pub struct ValueDeserializer<E> {
+    value: Value,
+    marker: PhantomData<E>,
+}
+
+impl<'de, E> Deserializer<'de> for ValueDeserializer<E>
+where
+    E: de::Error,
+{
+    type Error = E;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, E>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Value::Unit => visitor.visit_unit(),
+            Value::Bool(b) => visitor.visit_bool(b),
+            Value::Int(i) => match i64::try_from(i) {
+                Ok(i) => visitor.visit_i64(i),
+                Err(_) => visitor.visit_i128(i),
+            },
+            Value::Uint(u) => match u64::try_from(u) {
+                Ok(u) => visitor.visit_u64(u),
+                Err(_) => visitor.visit_u128(u),
+            },
+            Value::Float(f) => visitor.visit_f64(f),
+            Value::Char(c) => visitor.visit_char(c),
+            Value::Str(s) => visitor.visit_string(s),
+            Value::Bytes(bytes) => visitor.visit_byte_buf(bytes),
+            Value::None => visitor.visit_none(),
+            Value::Some(v) => visitor.visit_some(v.into_deserializer()),
+            Value::Newtype(v) => visitor.visit_newtype_struct(v.into_deserializer()),
+            Value::Seq(elements) => {
+                SeqDeserializer::new(elements.into_iter()).deserialize_any(visitor)
+            }
+            Value::Map(entries) => {
+                MapDeserializer::new(entries.into_iter()).deserialize_any(visitor)
+            }
+        }
+    }
+
+    // Formats without a separate representation for Some, such as JSON, only
+    // ever produce None.
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, E>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Value::None | Value::Unit => visitor.visit_none(),
+            Value::Some(v) => visitor.visit_some(v.into_deserializer()),
+            value => visitor.visit_some(value.into_deserializer()),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, E>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Value::Newtype(v) => visitor.visit_newtype_struct(v.into_deserializer()),
+            value => visitor.visit_newtype_struct(value.into_deserializer()),
+        }
+    }
+
+    // The inverse of ValueVisitor::visit_enum, and also accepts a string for
+    // a unit variant.
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, E>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Value::Str(variant) => visitor.visit_enum(variant.into_deserializer()),
+            Value::Map(mut entries) if entries.len() == 1 => {
+                let (variant, content) = entries.remove(0);
+                visitor.visit_enum(EnumDeserializer {
+                    variant,
+                    content,
+                    marker: PhantomData,
+                })
+            }
+            value => Err(E::invalid_type(value.unexpected(), &"enum")),
+        }
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, E>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier
+    }
+}
+
+impl Value {
+    pub(crate) fn unexpected(&self) -> Unexpected<'_> {
+        match self {
+            Value::Unit => Unexpected::Unit,
+            Value::Bool(b) => Unexpected::Bool(*b),
+            Value::Int(i) => match i64::try_from(*i) {
+                Ok(i) => Unexpected::Signed(i),
+                Err(_) => Unexpected::Other("integer"),
+            },
+            Value::Uint(u) => match u64::try_from(*u) {
+                Ok(u) => Unexpected::Unsigned(u),
+                Err(_) => Unexpected::Other("integer"),
+            },
+            Value::Float(f) => Unexpected::Float(*f),
+            Value::Char(c) => Unexpected::Char(*c),
+            Value::Str(s) => Unexpected::Str(s),
+            Value::Bytes(bytes) => Unexpected::Bytes(bytes),
+            Value::None | Value::Some(_) => Unexpected::Option,
+            Value::Newtype(_) => Unexpected::NewtypeStruct,
+            Value::Seq(_) => Unexpected::Seq,
+            Value::Map(_) => Unexpected::Map,
+        }
+    }
+}
+
+/// Enum recorded as a map with a single entry.
+struct EnumDeserializer<E> {
+    variant: Value,
+    content: Value,
+    marker: PhantomData<E>,
+}
+
+impl<'de, E> EnumAccess<'de> for EnumDeserializer<E>
+where
+    E: de::Error,
+{
+    type Error = E;
+    type Variant = ValueDeserializer<E>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, ValueDeserializer<E>), E>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((variant, self.content.into_deserializer()))
+    }
+}
+
+impl<'de, E> VariantAccess<'de> for ValueDeserializer<E>
+where
+    E: de::Error,
+{
+    type Error = E;
+
+    fn unit_variant(self) -> Result<(), E> {
+        match self.value {
+            Value::Unit => Ok(()),
+            value => Err(E::invalid_type(value.unexpected(), &"unit variant")),
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, E>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, E>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_any(visitor)
+    }
+
+    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, E>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_any(visitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::Int(i128::from(v)))
+    }
+
+    fn visit_i128<E>(self, v: i128) -> Result<Value, E> {
+        Ok(Value::Int(v))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Value, E> {
+        Ok(Value::Uint(u128::from(v)))
+    }
+
+    fn visit_u128<E>(self, v: u128) -> Result<Value, E> {
+        Ok(Value::Uint(v))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Value, E> {
+        Ok(Value::Float(v))
+    }
+
+    fn visit_char<E>(self, v: char) -> Result<Value, E> {
+        Ok(Value::Char(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::Str(String::from(v)))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Value, E> {
+        Ok(Value::Str(v))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Value, E> {
+        Ok(Value::Bytes(v.to_vec()))
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Value, E> {
+        Ok(Value::Bytes(v))
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Unit)
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::None)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Value::deserialize(deserializer).map(|v| Value::Some(Box::new(v)))
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Value::deserialize(deserializer).map(|v| Value::Newtype(Box::new(v)))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut elements = Vec::new();
+        while let Some(element) = seq.next_element()? {
+            elements.push(element);
+        }
+        Ok(Value::Seq(elements))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut entries = Vec::new();
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(Value::Map(entries))
+    }
+
+    // Same approach as serde's IgnoredAny, which is what would otherwise have
+    // consumed this value.
+    fn visit_enum<A>(self, data: A) -> Result<Value, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        let (variant, content) = data.variant::<Value>()?;
+        let content = content.newtype_variant::<Value>()?;
+        Ok(Value::Map(vec![(variant, content)]))
+    }
+}
+
+/// Renders in a compact, JSON-like syntax with Rust literals for strings,
+/// chars and bytes, like `{ "a": [1, 2.5, 'c'] }`.
+impl Display for Value {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Unit => formatter.write_str("()"),
+            Value::Bool(b) => Display::fmt(b, formatter),
+            Value::Int(i) => Display::fmt(i, formatter),
+            Value::Uint(u) => Display::fmt(u, formatter),
+            Value::Float(f) => Debug::fmt(f, formatter),
+            Value::Char(c) => Debug::fmt(c, formatter),
+            Value::Str(s) => Debug::fmt(s, formatter),
+            Value::Bytes(bytes) => write!(formatter, "b\"{}\"", bytes.escape_ascii()),
+            Value::None => formatter.write_str("None"),
+            Value::Some(v) => write!(formatter, "Some({})", v),
+            Value::Newtype(v) => Display::fmt(v, formatter),
+            Value::Seq(elements) => {
+                formatter.write_str("[")?;
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        formatter.write_str(", ")?;
+                    }
+                    Display::fmt(element, formatter)?;
+                }
+                formatter.write_str("]")
+            }
+            Value::Map(entries) => {
+                if entries.is_empty() {
+                    return formatter.write_str("{}");
+                }
+                formatter.write_str("{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        formatter.write_str(",")?;
+                    }
+                    write!(formatter, " {}: {}", key, value)?;
+                }
+                formatter.write_str(" }")
+            }
+        }
+    }
+}
+
+/// Serializer that records a value into a [`Value`], for buffering output that
+/// has to be reordered.
+pub(crate) struct ValueSerializer<E> {
+    marker: PhantomData<E>,
+}
+
+impl<E> ValueSerializer<E> {
+    pub(crate) fn new() -> Self {
+        ValueSerializer {
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<E> ser::Serializer for ValueSerializer<E>
+where
+    E: ser::Error,
+{
+    type Ok = Value;
+    type Error = E;
+    type SerializeSeq = SeqSerializer<E>;
+    type SerializeTuple = SeqSerializer<E>;
+    type SerializeTupleStruct = SeqSerializer<E>;
+    type SerializeTupleVariant = VariantSerializer<SeqSerializer<E>>;
+    type SerializeMap = MapSerializer<E>;
+    type SerializeStruct = MapSerializer<E>;
+    type SerializeStructVariant = VariantSerializer<MapSerializer<E>>;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, E> {
+        Ok(Value::Int(i128::from(v)))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, E> {
+        Ok(Value::Int(i128::from(v)))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, E> {
+        Ok(Value::Int(i128::from(v)))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, E> {
+        Ok(Value::Int(i128::from(v)))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Value, E> {
+        Ok(Value::Int(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, E> {
+        Ok(Value::Uint(u128::from(v)))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, E> {
+        Ok(Value::Uint(u128::from(v)))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, E> {
+        Ok(Value::Uint(u128::from(v)))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, E> {
+        Ok(Value::Uint(u128::from(v)))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Value, E> {
+        Ok(Value::Uint(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, E> {
+        Ok(Value::Float(f64::from(v)))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, E> {
+        Ok(Value::Float(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, E> {
+        Ok(Value::Char(v))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, E> {
+        Ok(Value::Str(v.to_owned()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, E> {
+        Ok(Value::Bytes(v.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<Value, E> {
+        Ok(Value::None)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Value, E>
+    where
+        T: ?Sized + Serialize,
+    {
+        let value = value.serialize(self)?;
+        Ok(Value::Some(Box::new(value)))
+    }
+
+    fn serialize_unit(self) -> Result<Value, E> {
+        Ok(Value::Unit)
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Value, E> {
+        let _ = name;
+        Ok(Value::Unit)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Value, E> {
+        let _ = (name, variant_index);
+        Ok(Value::Str(variant.to_owned()))
+    }
+
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Value, E>
+    where
+        T: ?Sized + Serialize,
+    {
+        let _ = name;
+        let value = value.serialize(self)?;
+        Ok(Value::Newtype(Box::new(value)))
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, E>
+    where
+        T: ?Sized + Serialize,
+    {
+        let _ = (name, variant_index);
+        let value = value.serialize(self)?;
+        Ok(Value::Map(vec![(Value::Str(variant.to_owned()), value)]))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer<E>, E> {
+        Ok(SeqSerializer {
+            elements: Vec::with_capacity(len.unwrap_or(0)),
+            marker: PhantomData,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer<E>, E> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, name: &'static str, len: usize) -> Result<SeqSerializer<E>, E> {
+        let _ = name;
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<VariantSerializer<SeqSerializer<E>>, E> {
+        let _ = (name, variant_index);
+        Ok(VariantSerializer {
+            variant,
+            content: self.serialize_seq(Some(len))?,
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<MapSerializer<E>, E> {
+        Ok(MapSerializer {
+            entries: Vec::with_capacity(len.unwrap_or(0)),
+            key: None,
+            marker: PhantomData,
+        })
+    }
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<MapSerializer<E>, E> {
+        let _ = name;
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<VariantSerializer<MapSerializer<E>>, E> {
+        let _ = (name, variant_index);
+        Ok(VariantSerializer {
+            variant,
+            content: self.serialize_map(Some(len))?,
+        })
+    }
+}
+
+pub(crate) struct SeqSerializer<E> {
+    elements: Vec<Value>,
+    marker: PhantomData<E>,
+}
+
+impl<E> SeqSerializer<E>
+where
+    E: ser::Error,
+{
+    fn push<T>(&mut self, value: &T) -> Result<(), E>
+    where
+        T: ?Sized + Serialize,
+    {
+        let value = value.serialize(ValueSerializer::new())?;
+        self.elements.push(value);
+        Ok(())
+    }
+}
+
+impl<E> ser::SerializeSeq for SeqSerializer<E>
+where
+    E: ser::Error,
+{
+    type Ok = Value;
+    type Error = E;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), E>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, E> {
+        Ok(Value::Seq(self.elements))
+    }
+}
+
+impl<E> ser::SerializeTuple for SeqSerializer<E>
+where
+    E: ser::Error,
+{
+    type Ok = Value;
+    type Error = E;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), E>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, E> {
+        Ok(Value::Seq(self.elements))
+    }
+}
+
+impl<E> ser::SerializeTupleStruct for SeqSerializer<E>
+where
+    E: ser::Error,
+{
+    type Ok = Value;
+    type Error = E;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), E>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, E> {
+        Ok(Value::Seq(self.elements))
+    }
+}
+
+pub(crate) struct MapSerializer<E> {
+    entries: Vec<(Value, Value)>,
+    key: Option<Value>,
+    marker: PhantomData<E>,
+}
+
+impl<E> ser::SerializeMap for MapSerializer<E>
+where
+    E: ser::Error,
+{
+    type Ok = Value;
+    type Error = E;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), E>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.key = Some(key.serialize(ValueSerializer::new())?);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), E>
+    where
+        T: ?Sized + Serialize,
+    {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| E::custom("serialize_value called before serialize_key"))?;
+        let value = value.serialize(ValueSerializer::new())?;
+        self.entries.push((key, value));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, E> {
+        Ok(Value::Map(self.entries))
+    }
+}
+
+impl<E> ser::SerializeStruct for MapSerializer<E>
+where
+    E: ser::Error,
+{
+    type Ok = Value;
+    type Error = E;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), E>
+    where
+        T: ?Sized + Serialize,
+    {
+        let value = value.serialize(ValueSerializer::new())?;
+        self.entries.push((Value::Str(key.to_owned()), value));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, E> {
+        Ok(Value::Map(self.entries))
+    }
+}
+
+/// Serializer for the content of a tuple or struct variant, recorded as a map
+/// with a single entry like any other enum.
+pub(crate) struct VariantSerializer<X> {
+    variant: &'static str,
+    content: X,
+}
+
+impl<X> VariantSerializer<X> {
+    fn wrap(variant: &'static str, content: Value) -> Value {
+        Value::Map(vec![(Value::Str(variant.to_owned()), content)])
+    }
+}
+
+impl<E> ser::SerializeTupleVariant for VariantSerializer<SeqSerializer<E>>
+where
+    E: ser::Error,
+{
+    type Ok = Value;
+    type Error = E;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), E>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.content.push(value)
+    }
+
+    fn end(self) -> Result<Value, E> {
+        let content = ser::SerializeSeq::end(self.content)?;
+        Ok(Self::wrap(self.variant, content))
+    }
+}
+
+impl<E> ser::SerializeStructVariant for VariantSerializer<MapSerializer<E>>
+where
+    E: ser::Error,
+{
+    type Ok = Value;
+    type Error = E;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), E>
+    where
+        T: ?Sized + Serialize,
+    {
+        ser::SerializeStruct::serialize_field(&mut self.content, key, value)
+    }
+
+    fn end(self) -> Result<Value, E> {
+        let content = ser::SerializeStruct::end(self.content)?;
+        Ok(Self::wrap(self.variant, content))
+    }
+}
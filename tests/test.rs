@@ -1,8 +1,15 @@
 #![allow(clippy::derive_partial_eq_without_eq, clippy::zero_sized_map_values)]
 
-use serde::Deserialize;
-use serde_derive::Deserialize;
+use serde::de::value::{MapAccessDeserializer, MapDeserializer};
+use serde::de::{IgnoredAny, IntoDeserializer, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use serde_derive::{Deserialize, Serialize};
+use serde_ignored::{
+    Allow, Flatten, Intentional, Key, Kind, Path, Reason, StrictError, Tagged, TaggedEnum, Tagging,
+    Value,
+};
 use std::collections::{BTreeMap as Map, BTreeSet as Set};
+use std::{fmt, iter};
 
 fn assert_ignored<'de, T>(json: &'de str, expected: &[&str]) -> T
 where
@@ -134,3 +141,1212 @@ fn test_unit_variant_key() {
     let ignored = &["First.unused"];
     assert_ignored::<Test>(json, ignored);
 }
+
+#[test]
+fn test_typed_key() {
+    #[derive(Debug, Deserialize)]
+    struct Test {
+        #[allow(dead_code)]
+        ints: Map<i32, V>,
+        #[allow(dead_code)]
+        uints: Map<u32, V>,
+        #[allow(dead_code)]
+        strings: Map<String, V>,
+        #[allow(dead_code)]
+        bools: Map<bool, V>,
+    }
+
+    let json = r#"{
+        "ints": {"-2": {"used": null, "unused": null}},
+        "uints": {"2": {"used": null, "unused": null}},
+        "strings": {"2": {"used": null, "unused": null}},
+        "bools": {"true": {"used": null, "unused": null}}
+    }"#;
+
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut keys = Vec::new();
+    let _: Test = serde_ignored::deserialize(de, |path| {
+        let Path::Map { parent, .. } = path else {
+            unreachable!();
+        };
+        let Path::Map { key, .. } = parent else {
+            unreachable!();
+        };
+        keys.push((key.clone(), path.to_string()));
+    })
+    .unwrap();
+
+    assert_eq!(
+        keys,
+        [
+            (Key::Int(-2), "ints.-2.unused".to_owned()),
+            (Key::Uint(2), "uints.2.unused".to_owned()),
+            (Key::Str("2".to_owned()), "strings.2.unused".to_owned()),
+            (Key::Bool(true), "bools.true.unused".to_owned()),
+        ],
+    );
+}
+
+#[test]
+fn test_non_string_key() {
+    #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+    struct AnyKey;
+
+    impl<'de> Deserialize<'de> for AnyKey {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            struct AnyKeyVisitor;
+
+            impl<'de> Visitor<'de> for AnyKeyVisitor {
+                type Value = AnyKey;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str("any key")
+                }
+
+                fn visit_f64<E>(self, _v: f64) -> Result<Self::Value, E> {
+                    Ok(AnyKey)
+                }
+
+                fn visit_bytes<E>(self, _v: &[u8]) -> Result<Self::Value, E> {
+                    Ok(AnyKey)
+                }
+
+                fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+                where
+                    A: SeqAccess<'de>,
+                {
+                    while seq.next_element::<serde_json::Value>()?.is_some() {}
+                    Ok(AnyKey)
+                }
+            }
+
+            deserializer.deserialize_any(AnyKeyVisitor)
+        }
+    }
+
+    fn assert_key_ignored<'de, K, T>(key: K, expected: &str)
+    where
+        K: IntoDeserializer<'de, serde_json::Error>,
+        T: Deserialize<'de>,
+    {
+        let value = serde_json::json!({"used": null, "unused": null});
+        let de = MapDeserializer::new(iter::once((key, value)));
+        let mut unused = Vec::new();
+        let _: T = serde_ignored::deserialize(de, |path| unused.push(path.to_string())).unwrap();
+        assert_eq!(unused, [expected]);
+    }
+
+    assert_key_ignored::<_, Map<char, V>>('c', "c.unused");
+    assert_key_ignored::<_, Map<AnyKey, V>>(serde_json::json!(1.5), "1.5.unused");
+    assert_key_ignored::<_, Map<AnyKey, V>>(&b"\xff\x00"[..], "ff00.unused");
+    assert_key_ignored::<_, Map<AnyKey, V>>(serde_json::json!([1, "a"]), "[1, a].unused");
+}
+
+#[test]
+fn test_composite_key() {
+    #[derive(Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+    struct Point {
+        x: i32,
+    }
+
+    let key = serde_json::json!({"x": 1, "typo": 2});
+    let value = serde_json::json!({"used": null, "unused": null});
+    let de = MapDeserializer::new(iter::once((key, value)));
+    let mut unused = Vec::new();
+    let _: Map<Point, V> =
+        serde_ignored::deserialize(de, |path| unused.push(path.to_string())).unwrap();
+    assert_eq!(unused, ["?key.typo", "{ typo: _, x: 1 }.unused"]);
+}
+
+#[test]
+fn test_variant() {
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    enum Shape {
+        Square(Side),
+        Circle { radius: u32 },
+    }
+
+    #[derive(Deserialize)]
+    struct Side {
+        #[allow(dead_code)]
+        side: u32,
+    }
+
+    let json = r#"[{"Circle": {"radius": 1, "typo": 2}}, {"Square": {"side": 1, "typo": 3}}]"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut unused = Vec::new();
+    let _: Vec<Shape> =
+        serde_ignored::deserialize(de, |path| unused.push(path.to_string())).unwrap();
+    assert_eq!(unused, ["0.Circle.typo", "1.Square.?.typo"]);
+
+    // Variant identified by its index rather than its name.
+    let value = serde_json::json!({"radius": 1, "typo": 2});
+    let map = MapDeserializer::<_, serde_json::Error>::new(iter::once((1u32, value)));
+    let de = MapAccessDeserializer::new(map);
+    let mut unused = Vec::new();
+    let _: Shape = serde_ignored::deserialize(de, |path| unused.push(path.to_string())).unwrap();
+    assert_eq!(unused, ["Circle.typo"]);
+}
+
+#[test]
+fn test_container_name() {
+    #[derive(Deserialize)]
+    struct Package {
+        #[allow(dead_code)]
+        dependencies: Map<String, Dependency>,
+        #[allow(dead_code)]
+        shape: Shape,
+    }
+
+    #[derive(Deserialize)]
+    struct Dependency {
+        #[allow(dead_code)]
+        version: String,
+    }
+
+    #[derive(Deserialize)]
+    enum Shape {
+        Circle {
+            #[allow(dead_code)]
+            radius: u32,
+        },
+    }
+
+    let json = r#"{
+        "dependencies": {"serde": {"version": "1.0", "typo1": ""}},
+        "shape": {"Circle": {"radius": 1, "typo2": 2}},
+        "typo3": {}
+    }"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut unused = Vec::new();
+    let _: Package = serde_ignored::deserialize_with_context(de, |path, context| {
+        unused.push((path.to_string(), context.container()));
+    })
+    .unwrap();
+    assert_eq!(
+        unused,
+        [
+            ("dependencies.serde.typo1".to_owned(), Some("Dependency")),
+            ("shape.Circle.typo2".to_owned(), Some("Shape")),
+            ("typo3".to_owned(), Some("Package")),
+        ],
+    );
+}
+
+#[test]
+fn test_expected_fields() {
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Package {
+        name: String,
+        version: String,
+        features: Map<String, V>,
+        shape: Option<Shape>,
+    }
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    enum Shape {
+        Circle { radius: u32 },
+    }
+
+    let json = r#"{
+        "name": "demo",
+        "version": "1.0",
+        "verison": "1.0",
+        "features": {},
+        "shape": {"Circle": {"radius": 1, "raduis": 1}}
+    }"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut messages = Vec::new();
+    let _: Package = serde_ignored::deserialize_with_context(de, |path, context| {
+        let expected = context.expected_fields().unwrap();
+        let expected = Vec::from_iter(expected.iter().map(|field| format!("`{field}`")));
+        messages.push(format!(
+            "unknown key `{}`, expected one of {}",
+            path,
+            expected.join(", "),
+        ));
+    })
+    .unwrap();
+    assert_eq!(
+        messages,
+        [
+            "unknown key `verison`, expected one of `name`, `version`, `features`, `shape`",
+            "unknown key `shape.?.Circle.raduis`, expected one of `radius`",
+        ],
+    );
+
+    let json =
+        r#"{"name": "demo", "version": "1.0", "features": {"a": {"used": null, "typo": 1}}}"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut expected = Vec::new();
+    let _: Package = serde_ignored::deserialize_with_context(de, |_path, context| {
+        expected.push(context.expected_fields());
+    })
+    .unwrap();
+    assert_eq!(expected, [Some(&["used"][..])]);
+}
+
+#[test]
+fn test_suggestion() {
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Package {
+        name: String,
+        version: String,
+        #[serde(default)]
+        dry_run: bool,
+        #[serde(default)]
+        features: Map<String, V>,
+        #[serde(default)]
+        metadata: IgnoredAny,
+    }
+
+    let json = r#"{
+        "name": "demo",
+        "version": "1.0",
+        "verison": "1.0",
+        "Features": {},
+        "dry-run": true,
+        "nmae": "demo",
+        "license": "MIT",
+        "v": "1.0",
+        "metadata": {}
+    }"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut suggestions = Vec::new();
+    let _: Package = serde_ignored::deserialize_with_context(de, |path, context| {
+        suggestions.push((path.to_string(), context.suggestion(&path)));
+    })
+    .unwrap();
+    assert_eq!(
+        suggestions,
+        [
+            ("verison".to_owned(), Some("version")),
+            ("Features".to_owned(), Some("features")),
+            ("dry-run".to_owned(), Some("dry_run")),
+            ("nmae".to_owned(), Some("name")),
+            ("license".to_owned(), None),
+            ("v".to_owned(), None),
+            ("metadata".to_owned(), None),
+        ],
+    );
+}
+
+#[test]
+fn test_fallible() {
+    #[derive(Debug, Deserialize)]
+    struct Package {
+        #[allow(dead_code)]
+        dependencies: Map<String, Dependency>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Dependency {
+        #[allow(dead_code)]
+        version: String,
+    }
+
+    let json = r#"{"dependencies": {"serde": {"version": "1.0", "typo1": "", "typo2": ""}}}"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut calls = 0;
+    let result: Result<Package, _> = serde_ignored::deserialize_fallible(de, |path| {
+        calls += 1;
+        Err(format!("unknown field `{}`", path.segment().unwrap()))
+    });
+    let err = result.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "dependencies.serde.typo1: unknown field `typo1` at line 1 column 55",
+    );
+    assert_eq!(calls, 1);
+
+    let json = r#"{"dependencies": {"serde": {"version": "1.0"}}}"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let result: Result<Package, _> =
+        serde_ignored::deserialize_fallible(de, |_path| Err("unknown field"));
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_strict() {
+    #[derive(Debug, Deserialize)]
+    struct Package {
+        #[allow(dead_code)]
+        dependencies: Map<String, Dependency>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Dependency {
+        #[allow(dead_code)]
+        version: String,
+    }
+
+    let json = r#"{
+        "dependencies": {"serde": {"version": "1.0", "typo1": ""}},
+        "typo2": {"inner": ""}
+    }"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let err = serde_ignored::deserialize_strict::<_, Package>(de).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "2 unknown keys: `dependencies.serde.typo1`, `typo2`",
+    );
+    let ignored = Vec::from_iter(err.ignored().iter().map(ToString::to_string));
+    assert_eq!(ignored, ["dependencies.serde.typo1", "typo2"]);
+
+    let json = r#"{"dependencies": {}, "typo": 1}"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let err = serde_ignored::deserialize_strict::<_, Package>(de).unwrap_err();
+    assert_eq!(err.to_string(), "unknown key `typo`");
+
+    let json = r#"{"dependencies": {"serde": {"version": "1.0"}}}"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let package: Package = serde_ignored::deserialize_strict(de).unwrap();
+    assert_eq!(package.dependencies.len(), 1);
+
+    let json = r#"{"dependencies": {"serde": {"typo": ""}}}"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let err = serde_ignored::deserialize_strict::<_, Package>(de).unwrap_err();
+    assert!(matches!(err, StrictError::Deserialize(_)));
+    assert!(err.ignored().is_empty());
+    assert!(std::error::Error::source(&err).is_some());
+}
+
+#[test]
+fn test_values() {
+    #[derive(Deserialize)]
+    struct Config {
+        #[allow(dead_code)]
+        timeout: u64,
+    }
+
+    let json = r#"{
+        "timeout": 5,
+        "timeout_ms": 500,
+        "retries": {"count": -3, "backoff": [1.5, "exp", null, true]},
+        "name": "a\"b"
+    }"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut ignored = Vec::new();
+    let _: Config = serde_ignored::deserialize_with_values(de, |path, value| {
+        ignored.push((path.to_string(), value));
+    })
+    .unwrap();
+
+    assert_eq!(ignored[0], ("timeout_ms".to_owned(), Value::Uint(500)));
+    assert_eq!(
+        ignored[1].1,
+        Value::Map(vec![
+            (Value::Str("count".to_owned()), Value::Int(-3)),
+            (
+                Value::Str("backoff".to_owned()),
+                Value::Seq(vec![
+                    Value::Float(1.5),
+                    Value::Str("exp".to_owned()),
+                    Value::Unit,
+                    Value::Bool(true),
+                ]),
+            ),
+        ]),
+    );
+
+    let rendered = Vec::from_iter(
+        ignored
+            .iter()
+            .map(|(path, value)| format!("{path} = {value}")),
+    );
+    assert_eq!(
+        rendered,
+        [
+            "timeout_ms = 500",
+            r#"retries = { "count": -3, "backoff": [1.5, "exp", (), true] }"#,
+            r#"name = "a\"b""#,
+        ],
+    );
+}
+
+// Stands in for a format like bincode, which knows the type of each value only
+// from what the Deserialize impl asks for.
+struct Opaque(u64);
+
+impl<'de> serde::Deserializer<'de> for Opaque {
+    type Error = serde::de::value::Error;
+
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(serde::de::Error::custom("deserialize_any is not supported"))
+    }
+
+    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_u64(self.0)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier
+    }
+}
+
+impl IntoDeserializer<'_, serde::de::value::Error> for Opaque {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+#[test]
+fn test_values_not_self_describing() {
+    #[derive(Deserialize)]
+    struct Config {
+        #[allow(dead_code)]
+        timeout: u64,
+    }
+
+    let input =
+        || MapDeserializer::new([("timeout", Opaque(5)), ("timeout_ms", Opaque(500))].into_iter());
+
+    let mut ignored = Vec::new();
+    let _: Config = serde_ignored::deserialize(input(), |path| {
+        ignored.push(path.to_string());
+    })
+    .unwrap();
+    assert_eq!(ignored, ["timeout_ms"]);
+
+    // Capturing the value needs deserialize_any.
+    let result: Result<Config, _> =
+        serde_ignored::deserialize_with_values(input(), |_path, _value| {});
+    let err = result.err().unwrap();
+    assert_eq!(err.to_string(), "deserialize_any is not supported");
+}
+
+#[test]
+fn test_preserving() {
+    #[derive(Deserialize, Serialize)]
+    struct Manifest {
+        package: Option<Package>,
+        dependencies: Map<String, Dependency>,
+        edition: Edition,
+        ports: Map<i32, Dependency>,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    struct Package {
+        name: String,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    struct Dependency {
+        version: String,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    enum Edition {
+        Old(u16),
+        New { year: u16 },
+    }
+
+    let json = r#"{
+        "package": {"name": "demo", "publish": false},
+        "dependencies": {
+            "serde": {"version": "1.0", "typo1": {"a": [1, 2]}}
+        },
+        "edition": {"New": {"year": 2021, "typo2": 0}},
+        "ports": {"2": {"version": "1", "typo4": 5}, "-3": {"version": "1", "typo5": 6}},
+        "typo3": null
+    }"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let (manifest, extras): (Manifest, _) = serde_ignored::deserialize_preserving(de).unwrap();
+
+    let paths = Vec::from_iter(extras.iter().map(|(path, _)| path.to_string()));
+    assert_eq!(
+        paths,
+        [
+            "dependencies.serde.typo1",
+            "edition.New.typo2",
+            "package.?.publish",
+            "ports.-3.typo5",
+            "ports.2.typo4",
+            "typo3",
+        ],
+    );
+
+    let mut out = Vec::new();
+    let ser = &mut serde_json::Serializer::new(&mut out);
+    manifest
+        .serialize(serde_ignored::Serializer::new(ser, &extras))
+        .unwrap();
+    let expected = r#"{"package":{"name":"demo","publish":false},"dependencies":{"serde":{"version":"1.0","typo1":{"a":[1,2]}}},"edition":{"New":{"year":2021,"typo2":0}},"ports":{"-3":{"version":"1","typo5":6},"2":{"version":"1","typo4":5}},"typo3":null}"#;
+    assert_eq!(String::from_utf8(out).unwrap(), expected);
+}
+
+#[test]
+fn test_preserving_allow() {
+    #[derive(Deserialize, Serialize)]
+    struct Package {
+        name: String,
+        metadata: Allow<Metadata>,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    struct Metadata {
+        docs: Option<String>,
+        nested: Option<Allow<Nested>>,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    struct Nested {
+        docs: Option<String>,
+    }
+
+    let json = r#"{
+        "name": "demo",
+        "metadata": {"docs": "none", "ci": {"os": ["linux"]}, "nested": {"docs": null, "x": 1}},
+        "typo": 1
+    }"#;
+
+    // Nothing inside the Allow is reported.
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut ignored = Vec::new();
+    let _: Package = serde_ignored::deserialize_with_values(de, |path, _value| {
+        ignored.push(path.to_string());
+    })
+    .unwrap();
+    assert_eq!(ignored, ["typo"]);
+
+    // But it is kept for writing back out.
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let (package, extras): (Package, _) = serde_ignored::deserialize_preserving(de).unwrap();
+    let paths = Vec::from_iter(extras.iter().map(|(path, _)| path.to_string()));
+    assert_eq!(paths, ["metadata.ci", "metadata.nested.?.x", "typo"]);
+
+    let mut out = Vec::new();
+    let ser = &mut serde_json::Serializer::new(&mut out);
+    package
+        .serialize(serde_ignored::Serializer::new(ser, &extras))
+        .unwrap();
+    let expected = r#"{"name":"demo","metadata":{"docs":"none","nested":{"docs":null,"x":1},"ci":{"os":["linux"]}},"typo":1}"#;
+    assert_eq!(String::from_utf8(out).unwrap(), expected);
+}
+
+#[test]
+fn test_kind() {
+    #[derive(Deserialize)]
+    struct Config {
+        #[allow(dead_code)]
+        name: String,
+    }
+
+    let json = r#"{
+        "name": "demo",
+        "typo1": {},
+        "typo2": {"a": 1, "b": [2, 3], "c": {}},
+        "typo3": [null, "x"],
+        "typo4": "",
+        "typo5": 1.5,
+        "typo6": null
+    }"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut ignored = Vec::new();
+    let _: Config = serde_ignored::deserialize_inspecting(de, |path, context| {
+        ignored.push((path.to_string(), context.kind(), context.size()));
+    })
+    .unwrap();
+
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut kinds = Vec::new();
+    let _: Config = serde_ignored::deserialize_with_context(de, |_path, context| {
+        kinds.push(context.kind());
+    })
+    .unwrap();
+    assert_eq!(kinds, [None; 6]);
+
+    assert_eq!(
+        ignored,
+        [
+            ("typo1".to_owned(), Some(Kind::Map), Some(0)),
+            ("typo2".to_owned(), Some(Kind::Map), Some(3)),
+            ("typo3".to_owned(), Some(Kind::Seq), Some(2)),
+            ("typo4".to_owned(), Some(Kind::String), None),
+            ("typo5".to_owned(), Some(Kind::Scalar), None),
+            ("typo6".to_owned(), Some(Kind::Null), None),
+        ],
+    );
+}
+
+#[test]
+fn test_reason() {
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Config {
+        placeholder: IgnoredAny,
+        shape: Shape,
+        first: First,
+        skipped: Vec<IgnoredAny>,
+        pair: (u32, IgnoredAny),
+        extensions: Map<String, IgnoredAny>,
+    }
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    enum Shape {
+        Circle { radius: u32 },
+    }
+
+    // Keeps the first element of a sequence and skips the rest.
+    struct First;
+
+    impl<'de> Deserialize<'de> for First {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            struct FirstVisitor;
+
+            impl<'de> Visitor<'de> for FirstVisitor {
+                type Value = First;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str("a sequence")
+                }
+
+                fn visit_seq<A>(self, mut seq: A) -> Result<First, A::Error>
+                where
+                    A: SeqAccess<'de>,
+                {
+                    seq.next_element::<u32>()?;
+                    while let Some(IgnoredAny) = seq.next_element()? {}
+                    Ok(First)
+                }
+            }
+
+            deserializer.deserialize_seq(FirstVisitor)
+        }
+    }
+
+    let json = r#"{
+        "placeholder": 0,
+        "shape": {"Circle": {"radius": 1, "typo1": 2}},
+        "first": [1, 2, 3],
+        "skipped": [6, 7],
+        "pair": [8, 9],
+        "extensions": {"x-a": 4},
+        "typo2": 5
+    }"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut ignored = Vec::new();
+    let _: Config = serde_ignored::deserialize_with_context(de, |path, context| {
+        ignored.push((path.to_string(), context.reason()));
+    })
+    .unwrap();
+
+    assert_eq!(
+        ignored,
+        [
+            ("placeholder".to_owned(), Reason::IgnoredAny),
+            ("shape.Circle.typo1".to_owned(), Reason::UnknownVariantField),
+            ("first.1".to_owned(), Reason::TrailingData),
+            ("first.2".to_owned(), Reason::TrailingData),
+            ("skipped.0".to_owned(), Reason::IgnoredAny),
+            ("skipped.1".to_owned(), Reason::IgnoredAny),
+            ("pair.1".to_owned(), Reason::IgnoredAny),
+            ("extensions.x-a".to_owned(), Reason::IgnoredAny),
+            ("typo2".to_owned(), Reason::UnknownField),
+        ],
+    );
+}
+
+#[test]
+fn test_other_variant() {
+    #[derive(Deserialize, Debug)]
+    struct Config {
+        #[allow(dead_code)]
+        log: Map<String, Level>,
+    }
+
+    #[derive(Deserialize, Debug)]
+    enum Level {
+        #[serde(rename = "info")]
+        Info,
+        #[serde(other)]
+        Unknown,
+    }
+
+    let json = r#"{"log": {"a": "info", "b": "verbose"}}"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut warnings = Vec::new();
+    let _: Config = serde_ignored::deserialize_with_context(de, |path, context| {
+        assert_eq!(context.reason(), Reason::OtherVariant);
+        warnings.push(format!(
+            "{path}: unknown variant {:?} mapped to fallback",
+            context.variant().unwrap(),
+        ));
+    })
+    .unwrap();
+
+    assert_eq!(
+        warnings,
+        [r#"log.b: unknown variant "verbose" mapped to fallback"#],
+    );
+
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let err = serde_ignored::deserialize_strict::<_, Config>(de).unwrap_err();
+    assert_eq!(err.to_string(), "unknown key `log.b`");
+}
+
+#[test]
+fn test_missing() {
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Package {
+        #[serde(alias = "package-name")]
+        name: String,
+        #[serde(default)]
+        edition: Option<String>,
+        dependencies: Map<String, Dependency>,
+    }
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    enum Dependency {
+        Registry {
+            version: String,
+            #[serde(default)]
+            features: Vec<String>,
+        },
+        Path(String),
+    }
+
+    let json = r#"{
+        "name": "demo",
+        "dependencies": {
+            "serde": {"Registry": {"version": "1.0", "typo": ""}},
+            "local": {"Path": "../local"}
+        }
+    }"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut ignored = Vec::new();
+    let mut missing = Vec::new();
+    let _: Package = serde_ignored::deserialize_with_missing(
+        de,
+        |path| ignored.push(path.to_string()),
+        |path| missing.push(path.to_string()),
+    )
+    .unwrap();
+
+    assert_eq!(ignored, ["dependencies.serde.Registry.typo"]);
+    assert_eq!(
+        missing,
+        [
+            "dependencies.serde.Registry.features",
+            "package-name",
+            "edition",
+        ]
+    );
+
+    // Aliases are listed among the fields, so whichever names of a field the
+    // input did not use are reported.
+    let json = r#"{"package-name": "demo", "dependencies": {}}"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut missing = Vec::new();
+    let _: Package = serde_ignored::deserialize_with_missing(
+        de,
+        |_path| {},
+        |path| {
+            missing.push(path.to_string());
+        },
+    )
+    .unwrap();
+    assert_eq!(missing, ["name", "edition"]);
+}
+
+#[test]
+fn test_missing_similar_names() {
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Server {
+        #[serde(default)]
+        host: Option<String>,
+        hosts: Vec<String>,
+        #[serde(default)]
+        name: Option<String>,
+        #[serde(default)]
+        name_full: Option<String>,
+    }
+
+    let json = r#"{"hosts": ["a", "b"], "name_full": "demo"}"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut missing = Vec::new();
+    let _: Server = serde_ignored::deserialize_with_missing(
+        de,
+        |_path| {},
+        |path| {
+            missing.push(path.to_string());
+        },
+    )
+    .unwrap();
+    assert_eq!(missing, ["host", "name"]);
+}
+
+#[test]
+fn test_duplicates() {
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Config {
+        dependencies: Map<String, u32>,
+        features: Map<String, u32>,
+    }
+
+    let json = r#"{
+        "dependencies": {"a": 1, "b": 2, "a": 3, "a": 4},
+        "features": {"a": 1},
+        "typo": {"x": 1, "x": 2}
+    }"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut ignored = Vec::new();
+    let mut duplicates = Vec::new();
+    let config: Config = serde_ignored::deserialize_with_duplicates(
+        de,
+        |path| ignored.push(path.to_string()),
+        |path| duplicates.push(path.to_string()),
+    )
+    .unwrap();
+
+    assert_eq!(config.dependencies["a"], 4);
+    assert_eq!(ignored, ["typo"]);
+    assert_eq!(duplicates, ["dependencies.a", "dependencies.a"]);
+}
+
+#[test]
+fn test_flatten() {
+    #[derive(Deserialize)]
+    struct Config {
+        servers: Vec<Flatten<Server, Tls>>,
+    }
+
+    #[derive(Deserialize)]
+    struct Server {
+        port: u16,
+        #[allow(dead_code)]
+        limits: Limits,
+    }
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Limits {
+        connections: u32,
+    }
+
+    #[derive(Deserialize)]
+    struct Tls {
+        cert: Option<String>,
+    }
+
+    let json = r#"{
+        "servers": [
+            {"port": 80, "limits": {"connections": 10, "typo1": 0}},
+            {"port": 443, "cert": "server.pem", "limits": {"connections": 10}, "typo2": 0}
+        ]
+    }"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut unused = Vec::new();
+    let config: Config = serde_ignored::deserialize_with_context(de, |path, context| {
+        unused.push((path.to_string(), context.reason()));
+    })
+    .unwrap();
+
+    let Flatten(server, tls) = &config.servers[1];
+    assert_eq!(server.port, 443);
+    assert_eq!(tls.cert.as_deref(), Some("server.pem"));
+    assert_eq!(
+        unused,
+        [
+            ("servers.0.limits.typo1".to_owned(), Reason::UnknownField),
+            ("servers.1.typo2".to_owned(), Reason::UnknownField),
+        ],
+    );
+
+    // Without serde_ignored, both sides are still deserialized.
+    let config: Config = serde_json::from_str(json).unwrap();
+    assert_eq!(config.servers[0].0.port, 80);
+}
+
+#[test]
+fn test_flatten_missing_and_duplicates() {
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Server {
+        port: u16,
+        #[serde(default)]
+        host: String,
+        env: Map<String, String>,
+    }
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Tls {
+        cert: Option<String>,
+        #[serde(default)]
+        env: Map<String, String>,
+    }
+
+    let json = r#"{"port": 80, "env": {"a": "1", "a": "2"}}"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut missing = Vec::new();
+    let _: Flatten<Server, Tls> = serde_ignored::deserialize_with_missing(
+        de,
+        |_path| {},
+        |path| {
+            missing.push(path.to_string());
+        },
+    )
+    .unwrap();
+    assert_eq!(missing, ["cert", "host"]);
+
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut duplicates = Vec::new();
+    let _: Flatten<Server, Tls> = serde_ignored::deserialize_with_duplicates(
+        de,
+        |_path| {},
+        |path| {
+            duplicates.push(path.to_string());
+        },
+    )
+    .unwrap();
+    assert_eq!(duplicates, ["env.a"]);
+}
+
+#[test]
+fn test_tagged() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    enum Plugin {
+        #[serde(rename = "http")]
+        Http(Http),
+        #[serde(rename = "file")]
+        File { path: String },
+        #[serde(rename = "none")]
+        None,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Http {
+        url: String,
+    }
+
+    impl TaggedEnum for Plugin {
+        const TAGGING: Tagging = Tagging::Internal { tag: "type" };
+    }
+
+    let json = r#"[
+        {"type": "http", "url": "https://example.com", "typo1": 0},
+        {"path": "/tmp", "typo2": 0, "type": "file"},
+        {"type": "none", "typo3": 0}
+    ]"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut unused = Vec::new();
+    let plugins: Vec<Tagged<Plugin>> =
+        serde_ignored::deserialize(de, |path| unused.push(path.to_string())).unwrap();
+
+    assert_eq!(
+        plugins[1],
+        Tagged(Plugin::File {
+            path: "/tmp".to_owned(),
+        }),
+    );
+    assert_eq!(unused, ["0.typo1", "1.typo2", "2.typo3"]);
+
+    let plugins: Vec<Tagged<Plugin>> = serde_json::from_str(json).unwrap();
+    assert_eq!(plugins[2], Tagged(Plugin::None));
+
+    let err = serde_json::from_str::<Tagged<Plugin>>(r#"{"url": ""}"#).unwrap_err();
+    assert_eq!(err.to_string(), "missing field `type`");
+}
+
+#[test]
+fn test_adjacently_tagged() {
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    enum Message {
+        Request { id: u32 },
+        Ping,
+    }
+
+    impl TaggedEnum for Message {
+        const TAGGING: Tagging = Tagging::Adjacent {
+            tag: "t",
+            content: "c",
+        };
+    }
+
+    let json = r#"[
+        {"t": "Request", "c": {"id": 1, "typo1": 0}, "typo2": 0},
+        {"t": "Ping"}
+    ]"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut unused = Vec::new();
+    let _: Vec<Tagged<Message>> = serde_ignored::deserialize_with_context(de, |path, context| {
+        unused.push((path.to_string(), context.reason()));
+    })
+    .unwrap();
+
+    assert_eq!(
+        unused,
+        [
+            ("0.typo2".to_owned(), Reason::UnknownField),
+            ("0.c.typo1".to_owned(), Reason::UnknownField),
+        ],
+    );
+}
+
+#[test]
+fn test_untagged() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    enum Source {
+        Git { git: String, branch: String },
+        Registry { version: String },
+        Path(String),
+    }
+
+    impl TaggedEnum for Source {
+        const TAGGING: Tagging = Tagging::Untagged;
+    }
+
+    let json = r#"{
+        "a": {"git": "https://example.com", "version": "1.0"},
+        "b": "../b",
+        "c": {"git": "https://example.com", "branch": "main", "typo": 0}
+    }"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut unused = Vec::new();
+    let sources: Map<String, Tagged<Source>> =
+        serde_ignored::deserialize(de, |path| unused.push(path.to_string())).unwrap();
+
+    assert_eq!(
+        sources["a"],
+        Tagged(Source::Registry {
+            version: "1.0".to_owned(),
+        }),
+    );
+    assert_eq!(sources["b"], Tagged(Source::Path("../b".to_owned())));
+    assert_eq!(unused, ["a.git", "c.typo"]);
+
+    let de = &mut serde_json::Deserializer::from_str("1");
+    let err = serde_ignored::deserialize::<_, _, Tagged<Source>>(de, |_| {}).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "data did not match any variant of untagged enum Source",
+    );
+}
+
+#[test]
+fn test_tagged_missing_and_duplicates() {
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    enum Plugin {
+        #[serde(rename = "http")]
+        Http {
+            url: String,
+            #[serde(default)]
+            timeout: u32,
+            headers: Map<String, String>,
+        },
+        #[serde(rename = "file")]
+        File { path: String },
+    }
+
+    impl TaggedEnum for Plugin {
+        const TAGGING: Tagging = Tagging::Internal { tag: "type" };
+    }
+
+    let json = r#"[
+        {"type": "http", "url": "https://example.com", "headers": {"a": "1", "a": "2"}},
+        {"type": "file", "path": "/tmp"}
+    ]"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut missing = Vec::new();
+    let _: Vec<Tagged<Plugin>> = serde_ignored::deserialize_with_missing(
+        de,
+        |_path| {},
+        |path| {
+            missing.push(path.to_string());
+        },
+    )
+    .unwrap();
+    assert_eq!(missing, ["0.timeout"]);
+
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut duplicates = Vec::new();
+    let _: Vec<Tagged<Plugin>> = serde_ignored::deserialize_with_duplicates(
+        de,
+        |_path| {},
+        |path| {
+            duplicates.push(path.to_string());
+        },
+    )
+    .unwrap();
+    assert_eq!(duplicates, ["0.headers.a"]);
+}
+
+#[test]
+fn test_allow() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Package {
+        name: String,
+        metadata: Allow<Metadata>,
+        #[serde(rename = "x-vendor")]
+        vendor: Intentional,
+        dependencies: Option<Allow<Map<String, Dependency>>>,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Metadata {
+        docs: bool,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Dependency {
+        version: String,
+    }
+
+    let json = r#"{
+        "name": "demo",
+        "metadata": {"docs": true, "ci": {"provider": "none"}},
+        "x-vendor": {"anything": [1, 2, 3]},
+        "dependencies": {"serde": {"version": "1.0", "typo": 0}},
+        "typo": 0
+    }"#;
+    let package: Package = assert_ignored(json, &["typo"]);
+    assert_eq!(package.metadata, Allow(Metadata { docs: true }));
+    assert_eq!(package.vendor, Intentional);
+    assert_eq!(package.dependencies.unwrap().0["serde"].version, "1.0");
+
+    let mut missing = Vec::new();
+    let de = &mut serde_json::Deserializer::from_str("{}");
+    let err = serde_ignored::deserialize_with_missing::<_, _, _, Allow<Metadata>>(
+        de,
+        |_| {},
+        |path| missing.push(path.to_string()),
+    )
+    .unwrap_err();
+    assert!(err.to_string().starts_with("missing field `docs`"));
+    assert!(missing.is_empty());
+}
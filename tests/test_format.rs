@@ -0,0 +1,161 @@
+#![allow(clippy::zero_sized_map_values)]
+
+use serde_derive::Deserialize;
+use serde_ignored::{Brackets, Dotted, Key, Lossless, PathBuf, PathFormatter, SegmentBuf};
+use std::collections::BTreeMap as Map;
+
+fn ignored_paths<F>(json: &str, style: F) -> Vec<String>
+where
+    F: PathFormatter,
+{
+    #[derive(Deserialize)]
+    struct Test {
+        #[allow(dead_code)]
+        servers: Option<Vec<Server>>,
+    }
+
+    #[derive(Deserialize)]
+    struct Server(#[allow(dead_code)] Map<String, Host>);
+
+    #[derive(Deserialize)]
+    struct Host {}
+
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut unused = Vec::new();
+    let _: Test = serde_ignored::deserialize(de, |path| {
+        unused.push(path.display_with(&style).to_string());
+    })
+    .unwrap();
+    unused
+}
+
+#[test]
+fn test_dotted() {
+    let json = r#"{"servers": [{"example.com": {"port": 1, "": 2, "x\"y": 3}}]}"#;
+
+    let unused = ignored_paths(json, Dotted::new());
+    assert_eq!(
+        unused,
+        [
+            r#"servers.?.0.?."example.com".port"#,
+            r#"servers.?.0.?."example.com"."""#,
+            r#"servers.?.0.?."example.com"."x\"y""#,
+        ],
+    );
+
+    let unused = ignored_paths(json, Dotted::new().hide_hops());
+    assert_eq!(unused[0], r#"servers.0."example.com".port"#);
+}
+
+#[test]
+fn test_brackets() {
+    let json = r#"{"servers": [{"example.com": {"port": 1}}], "typo": 1}"#;
+
+    let unused = ignored_paths(json, Brackets::new());
+    assert_eq!(
+        unused,
+        [r#"servers[?][0][?]["example.com"]["port"]"#, "typo"]
+    );
+
+    let unused = ignored_paths(json, Brackets::new().hide_hops());
+    assert_eq!(unused[0], r#"servers[0]["example.com"]["port"]"#);
+
+    let root = PathBuf::new();
+    assert_eq!(root.display_with(Brackets::new()).to_string(), ".");
+}
+
+#[test]
+fn test_lossless() {
+    let json = r#"{"servers": [{"a.b": {"": 1, " ": 2, "2": 3, "?": 4, "é\n": 5}}]}"#;
+
+    let unused = ignored_paths(json, Lossless);
+    assert_eq!(
+        unused,
+        [
+            r#"servers.?.0.?newtype."a.b"."""#,
+            r#"servers.?.0.?newtype."a.b"." ""#,
+            r#"servers.?.0.?newtype."a.b"."2""#,
+            r#"servers.?.0.?newtype."a.b"."?""#,
+            r#"servers.?.0.?newtype."a.b"."é\n""#,
+        ],
+    );
+
+    for rendered in &unused {
+        let path = PathBuf::from_lossless(rendered).unwrap();
+        assert_eq!(path.display_with(Lossless).to_string(), *rendered);
+    }
+
+    let path = PathBuf::from_lossless(r#"a."b.c".0."0".@Circle.?variant.?key."\u0001""#).unwrap();
+    assert_eq!(
+        path,
+        PathBuf::from_iter([
+            SegmentBuf::Map {
+                key: Key::from("a"),
+            },
+            SegmentBuf::Map {
+                key: Key::from("b.c"),
+            },
+            SegmentBuf::Seq { index: 0 },
+            SegmentBuf::Map {
+                key: Key::from("0"),
+            },
+            SegmentBuf::Variant {
+                name: "Circle".to_owned(),
+            },
+            SegmentBuf::NewtypeVariant,
+            SegmentBuf::MapKey,
+            SegmentBuf::Map {
+                key: Key::from("\u{1}"),
+            },
+        ]),
+    );
+    assert_eq!(PathBuf::from_lossless(".").unwrap(), PathBuf::new());
+
+    let err = PathBuf::from_lossless("a..b").unwrap_err();
+    assert_eq!(err.to_string(), "expected segment at offset 2");
+    let err = PathBuf::from_lossless(r#"a."b"#).unwrap_err();
+    assert_eq!(err.to_string(), "unterminated quoted key at offset 4");
+}
+
+#[test]
+fn test_lossless_typed_keys() {
+    let keys = [
+        Key::from("2"),
+        Key::Uint(2),
+        Key::Int(2),
+        Key::Int(-2),
+        Key::Bool(true),
+        Key::Char('\''),
+        Key::Float(2.0),
+        Key::Float(f64::NEG_INFINITY),
+        Key::Bytes(b"a\"\xff".to_vec()),
+        Key::Unit,
+        Key::Other("{..}".to_owned()),
+    ];
+
+    let rendered = Vec::from_iter(keys.iter().map(|key| {
+        let path = PathBuf::from_iter([SegmentBuf::Map { key: key.clone() }]);
+        path.display_with(Lossless).to_string()
+    }));
+    assert_eq!(
+        rendered,
+        [
+            r#""2""#,
+            "<2>",
+            "<+2>",
+            "<-2>",
+            "<true>",
+            r"<'\''>",
+            "<2.0>",
+            "<-inf>",
+            r#"<b"a\"\xff">"#,
+            "<()>",
+            r#"<"{..}">"#,
+        ],
+    );
+
+    for (key, rendered) in keys.iter().zip(&rendered) {
+        let path = PathBuf::from_lossless(rendered).unwrap();
+        assert_eq!(path.segments(), [SegmentBuf::Map { key: key.clone() }]);
+    }
+}
@@ -0,0 +1,267 @@
+use serde::Deserialize;
+use serde_derive::Deserialize;
+use serde_ignored::{Key, Path, PathBuf, PathPattern, Segment, SegmentBuf};
+use std::collections::BTreeSet as Set;
+
+#[derive(Deserialize)]
+struct Test {
+    #[allow(dead_code)]
+    list: Vec<Option<Inner>>,
+}
+
+#[derive(Deserialize)]
+struct Inner {}
+
+fn ignored_paths<'de, T>(json: &'de str) -> Set<PathBuf>
+where
+    T: Deserialize<'de>,
+{
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut unused = Set::new();
+    let _: T = serde_ignored::deserialize(de, |path| {
+        unused.insert(path.to_owned());
+    })
+    .unwrap();
+    unused
+}
+
+#[test]
+fn test_to_owned() {
+    let json = r#"{"list": [{}, {"typo": 1}]}"#;
+    let unused = ignored_paths::<Test>(json);
+
+    let expected = PathBuf::from_iter([
+        SegmentBuf::Map {
+            key: Key::from("list"),
+        },
+        SegmentBuf::Seq { index: 1 },
+        SegmentBuf::Some,
+        SegmentBuf::Map {
+            key: Key::from("typo"),
+        },
+    ]);
+    assert_eq!(unused, Set::from([expected.clone()]));
+    assert_eq!(expected.to_string(), "list.1.?.typo");
+}
+
+#[test]
+fn test_path_buf() {
+    let mut path = PathBuf::new();
+    assert!(path.is_root());
+    assert_eq!(path.to_string(), ".");
+    assert_eq!(path.parent(), None);
+
+    path.push(SegmentBuf::Map {
+        key: Key::from("servers"),
+    });
+    path.push(SegmentBuf::Seq { index: 0 });
+    let base = path.clone();
+    path.push(SegmentBuf::NewtypeStruct);
+    assert_eq!(path.to_string(), "servers.0.?");
+    assert!(path.starts_with(&base));
+    assert!(path.starts_with(&PathBuf::new()));
+    assert!(!base.starts_with(&path));
+    assert_eq!(path.parent(), Some(base.clone()));
+    assert!(base < path);
+
+    assert_eq!(path.pop(), Some(SegmentBuf::NewtypeStruct));
+    assert_eq!(path, base);
+}
+
+#[test]
+fn test_segments() {
+    let json = r#"{"list": [{"typo": 1}]}"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut checked = false;
+    let _: Test = serde_ignored::deserialize(de, |path| {
+        assert_eq!(path.depth(), 4);
+        let segments = Vec::from_iter(path.segments());
+        assert_eq!(
+            segments,
+            [
+                Segment::Map {
+                    key: &Key::from("list")
+                },
+                Segment::Seq { index: 0 },
+                Segment::Some,
+                Segment::Map {
+                    key: &Key::from("typo")
+                },
+            ],
+        );
+        assert!(path.segments().rev().eq(segments.iter().rev().copied()));
+        assert_eq!(
+            path.segment(),
+            Some(Segment::Map {
+                key: &Key::from("typo")
+            })
+        );
+
+        let ancestors = Vec::from_iter(path.ancestors().map(ToString::to_string));
+        assert_eq!(
+            ancestors,
+            ["list.0.?.typo", "list.0.?", "list.0", "list", "."]
+        );
+        assert!(path.to_owned().iter().eq(path.segments()));
+        checked = true;
+    })
+    .unwrap();
+    assert!(checked);
+}
+
+#[test]
+fn test_segments_double_ended() {
+    let a = Key::from("a");
+    let d = Key::from("d");
+    let root = Path::Root;
+    let p1 = Path::Map {
+        parent: &root,
+        key: a.clone(),
+    };
+    let p2 = Path::Seq {
+        parent: &p1,
+        index: 1,
+    };
+    let p3 = Path::Some { parent: &p2 };
+    let p4 = Path::Map {
+        parent: &p3,
+        key: d.clone(),
+    };
+    let p5 = Path::Seq {
+        parent: &p4,
+        index: 5,
+    };
+    let path = Path::NewtypeStruct { parent: &p5 };
+
+    let mut segments = path.segments();
+    assert_eq!(segments.len(), 6);
+    assert_eq!(segments.next(), Some(Segment::Map { key: &a }));
+    assert_eq!(segments.next_back(), Some(Segment::NewtypeStruct));
+    assert_eq!(segments.next_back(), Some(Segment::Seq { index: 5 }));
+    assert_eq!(segments.len(), 3);
+    assert_eq!(segments.next(), Some(Segment::Seq { index: 1 }));
+    assert_eq!(segments.clone().nth(1), Some(Segment::Map { key: &d }));
+    assert_eq!(segments.clone().nth(2), None);
+    assert_eq!(segments.clone().nth_back(1), Some(Segment::Some));
+    assert_eq!(segments.next_back(), Some(Segment::Map { key: &d }));
+    assert_eq!(segments.next(), Some(Segment::Some));
+    assert_eq!(segments.next(), None);
+    assert_eq!(segments.next_back(), None);
+
+    assert!(path
+        .segments()
+        .rev()
+        .eq(Vec::from_iter(path.segments()).into_iter().rev()));
+}
+
+#[test]
+fn test_json_pointer() {
+    let json = r#"{"list": [{"a/b~c": 1}]}"#;
+    let unused = ignored_paths::<Test>(json);
+    let path = unused.into_iter().next().unwrap();
+    assert_eq!(path.to_json_pointer(), "/list/0/a~1b~0c");
+    assert_eq!(PathBuf::new().to_json_pointer(), "");
+
+    let parsed = PathBuf::from_json_pointer("/list/0/a~1b~0c").unwrap();
+    let expected = PathBuf::from_iter([
+        SegmentBuf::Map {
+            key: Key::from("list"),
+        },
+        SegmentBuf::Seq { index: 0 },
+        SegmentBuf::Map {
+            key: Key::from("a/b~c"),
+        },
+    ]);
+    assert_eq!(parsed, expected);
+
+    let parsed = PathBuf::from_json_pointer("/01//").unwrap();
+    let expected = PathBuf::from_iter(["01", "", ""].map(|key| SegmentBuf::Map {
+        key: Key::from(key),
+    }));
+    assert_eq!(parsed, expected);
+
+    let err = PathBuf::from_json_pointer("list").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "JSON Pointer must start with '/' at offset 0"
+    );
+    let err = PathBuf::from_json_pointer("/a/b~2").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "'~' must be followed by '0' or '1' at offset 4"
+    );
+}
+
+#[test]
+fn test_pattern() {
+    let json = r#"{"list": [{"x-a": 1, "comment": 2, "example.com": 3}], "x-b": 4}"#;
+    let de = &mut serde_json::Deserializer::from_str(json);
+    let mut matched = Vec::new();
+    let patterns = [
+        "list.[0-9].x-*",
+        "**.comment",
+        "list.*.example\\.com",
+        "x-?",
+        "**",
+        "list.[!0].*",
+        "list.*",
+        "example.com",
+    ]
+    .map(|pattern| PathPattern::new(pattern).unwrap());
+    let _: Test = serde_ignored::deserialize(de, |path| {
+        let owned = path.to_owned();
+        for pattern in &patterns {
+            assert_eq!(path.matches(pattern), owned.matches(pattern));
+            if path.matches(pattern) {
+                matched.push(format!("{path} ~ {pattern}"));
+            }
+        }
+    })
+    .unwrap();
+    assert_eq!(
+        matched,
+        [
+            "list.0.?.x-a ~ list.[0-9].x-*",
+            "list.0.?.x-a ~ **",
+            "list.0.?.comment ~ **.comment",
+            "list.0.?.comment ~ **",
+            "list.0.?.example.com ~ list.*.example\\.com",
+            "list.0.?.example.com ~ **",
+            "x-b ~ x-?",
+            "x-b ~ **",
+        ],
+    );
+
+    assert!(PathBuf::new().matches(&"**".parse().unwrap()));
+    assert!(!PathBuf::new().matches(&"*".parse().unwrap()));
+    let path = PathBuf::from_iter([SegmentBuf::Seq { index: 12 }]);
+    assert!(path.matches(&"1[0-9]".parse().unwrap()));
+    assert!(!path.matches(&"[0-9]".parse().unwrap()));
+
+    let path = PathBuf::from_iter(["a", "b", "a", "c"].map(|key| SegmentBuf::Map {
+        key: Key::from(key),
+    }));
+    for (pattern, expected) in [
+        ("a.**.c", true),
+        ("**.a.c", true),
+        ("a.**.a.**.c", true),
+        ("**.b.**", true),
+        ("a.**.b", false),
+        ("a.c.**", false),
+        ("a.b.a.c.**", true),
+    ] {
+        let pattern: PathPattern = pattern.parse().unwrap();
+        assert_eq!(path.matches(&pattern), expected, "{pattern}");
+    }
+
+    for (pattern, err) in [
+        ("", "empty segment at offset 0"),
+        ("a..b", "empty segment at offset 2"),
+        ("a.[0-9", "unclosed '[' at offset 2"),
+        ("[]", "empty character class at offset 1"),
+        ("[9-0]", "character range out of order at offset 2"),
+        ("a\\", "expected a character after '\\' at offset 1"),
+    ] {
+        assert_eq!(PathPattern::new(pattern).unwrap_err().to_string(), err);
+    }
+}